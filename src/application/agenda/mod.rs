@@ -0,0 +1,43 @@
+use crate::application::backend::*;
+
+#[derive(Clone)]
+pub struct Event {
+    pub name: String,
+    pub at: Instant,
+}
+
+// Events the GM scheduled on the campaign timeline, in chronological order
+pub struct Agenda {
+    events: Vec<Event>,
+}
+
+impl Agenda {
+    pub fn new() -> Agenda {
+        Agenda { events: Vec::new() }
+    }
+
+    pub fn add(&mut self, event: Event) {
+        let index = self
+            .events
+            .iter()
+            .position(|x| x.at > event.at)
+            .unwrap_or_else(|| self.events.len());
+        self.events.insert(index, event);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Event {
+        self.events.remove(index)
+    }
+
+    pub fn events(&self) -> &Vec<Event> {
+        &self.events
+    }
+
+    // Removes and returns the first event after `from` up to and including `to`
+    pub fn take_next(&mut self, from: Instant, to: Instant) -> Option<Event> {
+        self.events
+            .iter()
+            .position(|x| x.at > from && x.at <= to)
+            .map(|x| self.events.remove(x))
+    }
+}
@@ -1,5 +1,10 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::hash::{Hash, Hasher};
 
+use crate::application::dice::{Dice, DiceRoller};
+use crate::application::formula::Formula;
+
 #[derive(Clone)]
 pub enum Stat {
     Attribute(&'static str, &'static str),
@@ -30,24 +35,57 @@ impl Hash for Stat {
     }
 }
 
+impl Stat {
+    pub fn identifier(&self) -> &'static str {
+        match self {
+            Stat::Attribute(_, short) => *short,
+            Stat::Ability(name, _) => *name,
+            Stat::Calculated(name) => *name,
+        }
+    }
+}
+
 pub struct StatDescription {
     pub stat: Stat,
     pub min: i32,
     pub max: i32,
+    // pools like LeP can be spent during play, their maximum is the
+    // calculated value
+    pub pool: bool,
+    // added to the value of derived stats like SK or INI
+    pub formula: Option<Formula>,
 }
 
 pub trait ModifierValue {
     fn name(&self) -> String;
     fn get_modifier(&self, s: &Stat, val: i32) -> i32;
+
+    // Rule limits like the maximum attribute value of an experience level
+    fn get_limit(&self, _: &str) -> Option<i32> {
+        None
+    }
+
+    // Minimum stat values a player needs for this value, e.g. for a profession
+    fn prerequisites(&self) -> Vec<(String, i32)> {
+        Vec::new()
+    }
+
+    // Special abilities that come with this value
+    fn abilities(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 pub struct Modifier {
     pub name: &'static str,
-    values: fn(&Player) -> Vec<Box<ModifierValue>>,
+    values: Box<Fn(&Player) -> Vec<Box<ModifierValue>>>,
 }
 
 impl Modifier {
-    pub fn new(name: &'static str, values: fn(&Player) -> Vec<Box<ModifierValue>>) -> Modifier {
+    pub fn new(
+        name: &'static str,
+        values: Box<Fn(&Player) -> Vec<Box<ModifierValue>>>,
+    ) -> Modifier {
         Modifier {
             name: name,
             values: values,
@@ -76,12 +114,8 @@ impl StatCategory {
         }
     }
 
-    pub fn add_stat(&mut self, stat: Stat, min: i32, max: i32) {
-        self.entries.push(CategoryEntry::Stat(StatDescription {
-            stat: stat,
-            min: min,
-            max: max,
-        }));
+    pub fn add_stat(&mut self, description: StatDescription) {
+        self.entries.push(CategoryEntry::Stat(description));
     }
 
     pub fn add_modifier(&mut self, modifier: Modifier) {
@@ -90,31 +124,137 @@ impl StatCategory {
 
     pub fn find_stat(&self, name: &String) -> Option<&StatDescription> {
         self.entries.iter().find_map(|x| match x {
-            CategoryEntry::Stat(description) => match description.stat {
-                Stat::Attribute(_, identifier) if identifier == name => Some(description),
-                Stat::Ability(identifier, _) if identifier == name => Some(description),
-                Stat::Calculated(identifier) if identifier == name => Some(description),
-                _ => None
-            },
-            CategoryEntry::Modifier(_) => None,
+            CategoryEntry::Stat(description) if description.stat.identifier() == name.as_str() => {
+                Some(description)
+            }
+            _ => None,
         })
     }
 }
 
+// The itemised parts of a calculated value, starting with the value stored
+// for the player. Parts that don't change the value are left out.
+#[derive(PartialEq)]
+pub struct Breakdown {
+    parts: Vec<(String, i32)>,
+}
+
+impl Breakdown {
+    pub fn new(value: i32) -> Breakdown {
+        Breakdown {
+            parts: vec![("Wert".to_string(), value)],
+        }
+    }
+
+    pub fn add<S: Into<String>>(&mut self, name: S, value: i32) {
+        if value != 0 {
+            self.parts.push((name.into(), value));
+        }
+    }
+
+    pub fn add_effects(&mut self, p: &Player, target: Option<&Stat>) {
+        for effect in p.effects().iter().filter(|x| x.target.as_ref() == target) {
+            self.add(effect.name.clone(), effect.value);
+        }
+    }
+
+    // Replaces all parts, for values that aren't built on the stored one
+    pub fn set<S: Into<String>>(&mut self, name: S, value: i32) {
+        self.parts.clear();
+        self.parts.push((name.into(), value));
+    }
+
+    pub fn parts(&self) -> &Vec<(String, i32)> {
+        &self.parts
+    }
+
+    pub fn total(&self) -> i32 {
+        self.parts.iter().map(|x| x.1).sum()
+    }
+}
+
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (name, value)) in self.parts.iter().enumerate() {
+            match i {
+                0 => writeln!(f, "{}: {}", name, value)?,
+                _ => writeln!(f, "{}: {:+}", name, value)?,
+            }
+        }
+        write!(f, "= {}", self.total())
+    }
+}
+
 pub struct CharacterSheet {
     categories: Vec<StatCategory>,
+    // (stat, stat it is calculated from) for rules that aren't formulas
+    dependencies: Vec<(Stat, Stat)>,
+    // stats that read the player's conditions or the effects on all checks
+    state_dependencies: Vec<Stat>,
 
-    calc: fn(&CharacterSheet, &Player, &StatCategory, &Stat) -> i32,
+    // the last argument returns the values of the stats a calculation reads
+    calc: Box<Fn(&CharacterSheet, &Player, &StatCategory, &Stat, &Fn(&Stat) -> i32) -> Breakdown>,
 }
 
 impl CharacterSheet {
-    pub fn new(calc: fn(&CharacterSheet, &Player, &StatCategory, &Stat) -> i32) -> CharacterSheet {
+    pub fn new(
+        calc: Box<
+            Fn(&CharacterSheet, &Player, &StatCategory, &Stat, &Fn(&Stat) -> i32) -> Breakdown,
+        >,
+    ) -> CharacterSheet {
         CharacterSheet {
             categories: Vec::new(),
+            dependencies: Vec::new(),
+            state_dependencies: Vec::new(),
             calc: calc,
         }
     }
 
+    pub fn add_dependency(&mut self, stat: Stat, on: Stat) {
+        self.dependencies.push((stat, on));
+    }
+
+    pub fn add_state_dependency(&mut self, stat: Stat) {
+        self.state_dependencies.push(stat);
+    }
+
+    pub fn state_dependencies(&self) -> &Vec<Stat> {
+        &self.state_dependencies
+    }
+
+    // The stats whose values the calculation of the given stat reads
+    pub fn dependencies(&self, s: &Stat) -> Vec<Stat> {
+        let mut stats: Vec<Stat> = self
+            .dependencies
+            .iter()
+            .filter(|x| x.0 == *s)
+            .map(|x| x.1.clone())
+            .collect();
+
+        let description = self.find_stat(&s.identifier().to_string());
+        if let Some(formula) = description.and_then(|x| x.formula.as_ref()) {
+            for name in formula.references() {
+                if let Some(stat) = self.find_stat(&name) {
+                    stats.push(stat.stat.clone());
+                }
+            }
+        }
+
+        stats
+    }
+
+    pub fn stats(&self) -> Vec<(&StatCategory, &StatDescription)> {
+        self.categories
+            .iter()
+            .flat_map(|c| {
+                c.entries.iter().filter_map(move |x| match x {
+                    CategoryEntry::Stat(stat) => Some((c, stat)),
+                    CategoryEntry::Modifier(_) => None,
+                })
+            })
+            .collect()
+    }
+
     pub fn add_category(&mut self, s: StatCategory) {
         self.categories.push(s);
     }
@@ -129,6 +269,21 @@ impl CharacterSheet {
         return None;
     }
 
+    pub fn find_stat(&self, name: &String) -> Option<&StatDescription> {
+        self.categories.iter().find_map(|x| x.find_stat(name))
+    }
+
+    pub fn modifiers(&self) -> Vec<&Modifier> {
+        self.categories
+            .iter()
+            .flat_map(|x| x.entries.iter())
+            .filter_map(|x| match x {
+                CategoryEntry::Modifier(modifier) => Some(modifier),
+                CategoryEntry::Stat(_) => None,
+            })
+            .collect()
+    }
+
     pub fn categories(&self) -> &Vec<StatCategory> {
         &self.categories
     }
@@ -138,7 +293,225 @@ impl CharacterSheet {
     }
 
     pub fn calc_value(&self, p: &Player, c: &StatCategory, s: &Stat) -> i32 {
-        (self.calc)(&self, p, c, s)
+        self.calc_breakdown(p, c, s).total()
+    }
+
+    // Calculates the stat along with all stats it depends on
+    pub fn calc_breakdown(&self, p: &Player, c: &StatCategory, s: &Stat) -> Breakdown {
+        let value = |s: &Stat| {
+            let name = s.identifier().to_string();
+            self.categories
+                .iter()
+                .find_map(|c| c.find_stat(&name).map(|x| self.calc_value(p, c, &x.stat)))
+                .unwrap_or(0)
+        };
+        (self.calc)(&self, p, c, s, &value)
+    }
+
+    // Calculates the stat from the given values of the stats it depends on
+    pub fn calc_breakdown_from(
+        &self,
+        p: &Player,
+        c: &StatCategory,
+        s: &Stat,
+        value: &Fn(&Stat) -> i32,
+    ) -> Breakdown {
+        (self.calc)(&self, p, c, s, value)
+    }
+
+    pub fn evaluate(&self, formula: &Formula, value: &Fn(&Stat) -> i32) -> i32 {
+        formula.evaluate(&|name| match self.find_stat(&name.to_string()) {
+            Some(description) => value(&description.stat),
+            None => 0,
+        })
+    }
+
+    // Adds every summand of the formula of the stat to the breakdown
+    pub fn add_formula(&self, val: &mut Breakdown, s: &Stat, value: &Fn(&Stat) -> i32) {
+        let description = self.find_stat(&s.identifier().to_string());
+        if let Some(formula) = description.and_then(|x| x.formula.as_ref()) {
+            for term in formula.terms() {
+                let value = self.evaluate(&term, value);
+                match term.references().is_empty() {
+                    true => val.add("Basis", value),
+                    false => val.add(term.to_string(), value),
+                }
+            }
+        }
+    }
+}
+
+// The calculated values of one player's stats. Changing a value, pool or
+// modifier through the cache only recalculates the stats depending on it,
+// reading the values of their dependencies from the cache.
+pub struct StatCache {
+    dependencies: HashMap<Stat, Vec<Stat>>,
+    dependents: HashMap<Stat, Vec<Stat>>,
+    values: HashMap<Stat, Breakdown>,
+}
+
+impl StatCache {
+    pub fn new(sheet: &CharacterSheet, p: &Player) -> StatCache {
+        let mut dependencies = HashMap::new();
+        let mut dependents = HashMap::new();
+        for (_, description) in sheet.stats() {
+            for dependency in sheet.dependencies(&description.stat) {
+                dependents
+                    .entry(dependency.clone())
+                    .or_insert_with(Vec::new)
+                    .push(description.stat.clone());
+                dependencies
+                    .entry(description.stat.clone())
+                    .or_insert_with(Vec::new)
+                    .push(dependency);
+            }
+        }
+
+        let mut cache = StatCache {
+            dependencies: dependencies,
+            dependents: dependents,
+            values: HashMap::new(),
+        };
+        let stats = sheet.stats().iter().map(|x| x.1.stat.clone()).collect();
+        cache.recalculate(sheet, p, stats);
+
+        cache
+    }
+
+    pub fn get(&self, s: &Stat) -> Option<&Breakdown> {
+        self.values.get(s)
+    }
+
+    // All of these return the stats whose breakdown changed
+    pub fn set_value(
+        &mut self,
+        sheet: &CharacterSheet,
+        p: &mut Player,
+        s: Stat,
+        val: i32,
+    ) -> Vec<Stat> {
+        p.set_value(s.clone(), val);
+        self.recalculate(sheet, p, vec![s])
+    }
+
+    // The pool itself is part of the result, its current value changed
+    pub fn set_spent(
+        &mut self,
+        sheet: &CharacterSheet,
+        p: &mut Player,
+        s: Stat,
+        val: i32,
+    ) -> Vec<Stat> {
+        p.set_spent(s.clone(), val);
+
+        let mut changed = self.recalculate(sheet, p, vec![s.clone()]);
+        if !changed.contains(&s) {
+            changed.push(s);
+        }
+        changed
+    }
+
+    pub fn set_modifier(
+        &mut self,
+        sheet: &CharacterSheet,
+        p: &mut Player,
+        name: String,
+        modifier: Box<ModifierValue>,
+    ) -> Vec<Stat> {
+        let affected = sheet
+            .stats()
+            .into_iter()
+            .map(|x| x.1.stat.clone())
+            .filter(|s| {
+                let val = p.get_value(s);
+                p.get_modifier(&name).get_modifier(s, val) != modifier.get_modifier(s, val)
+            })
+            .collect();
+
+        p.set_modifier(name, modifier);
+        self.recalculate(sheet, p, affected)
+    }
+
+    // Recalculates the stats and everything depending on them, each after
+    // the stats it depends on. Changes made to the player without the cache,
+    // like effects, conditions or regenerated pools, have to be passed here.
+    pub fn recalculate(
+        &mut self,
+        sheet: &CharacterSheet,
+        p: &Player,
+        stats: Vec<Stat>,
+    ) -> Vec<Stat> {
+        let mut dirty = HashSet::new();
+        let mut queue = stats;
+        while let Some(stat) = queue.pop() {
+            if let Some(dependents) = self.dependents.get(&stat) {
+                queue.extend(dependents.iter().filter(|x| !dirty.contains(*x)).cloned());
+            }
+            dirty.insert(stat);
+        }
+
+        let mut changed = Vec::new();
+        for (category, description) in self.order(sheet, &dirty) {
+            let breakdown = {
+                let values = &self.values;
+                let value = |s: &Stat| values.get(s).map(|x| x.total()).unwrap_or(0);
+                sheet.calc_breakdown_from(p, category, &description.stat, &value)
+            };
+
+            if self.values.get(&description.stat) != Some(&breakdown) {
+                changed.push(description.stat.clone());
+                self.values.insert(description.stat.clone(), breakdown);
+            }
+        }
+
+        changed
+    }
+
+    // The given stats of the sheet in topological order. Rulesets reject
+    // cyclic formulas, so every stat is visited once.
+    fn order<'a>(
+        &self,
+        sheet: &'a CharacterSheet,
+        stats: &HashSet<Stat>,
+    ) -> Vec<(&'a StatCategory, &'a StatDescription)> {
+        fn visit<'a>(
+            cache: &StatCache,
+            entries: &Vec<(&'a StatCategory, &'a StatDescription)>,
+            stats: &HashSet<Stat>,
+            s: &Stat,
+            visited: &mut HashSet<Stat>,
+            order: &mut Vec<(&'a StatCategory, &'a StatDescription)>,
+        ) {
+            if !stats.contains(s) || !visited.insert(s.clone()) {
+                return;
+            }
+
+            if let Some(dependencies) = cache.dependencies.get(s) {
+                for dependency in dependencies {
+                    visit(cache, entries, stats, dependency, visited, order);
+                }
+            }
+
+            if let Some(entry) = entries.iter().find(|x| x.1.stat == *s) {
+                order.push(*entry);
+            }
+        }
+
+        let entries = sheet.stats();
+        let mut visited = HashSet::new();
+        let mut order = Vec::new();
+        for (_, description) in &entries {
+            visit(
+                self,
+                &entries,
+                stats,
+                &description.stat,
+                &mut visited,
+                &mut order,
+            );
+        }
+
+        order
     }
 }
 
@@ -152,6 +525,83 @@ pub enum TimeUnits {
     Years(i32),
 }
 
+// A point in time as (year, month, day, hour, minute), which orders
+// chronologically as long as months are numbered in order
+pub type Instant = (i32, i32, i32, i32, i32);
+
+pub fn now(calendar: &PenAndPaperCalendar) -> Instant {
+    let (day, month, year) = calendar.get_date();
+    let (hour, minute) = calendar.get_time();
+    (year, month, day, hour, minute)
+}
+
+pub fn after(calendar: &mut PenAndPaperCalendar, t: TimeUnits) -> Instant {
+    let (day, month, year) = calendar.get_date();
+    let (hour, minute) = calendar.get_time();
+
+    calendar.advance_time(t);
+    let instant = now(calendar);
+
+    calendar.set_date(day, month, year);
+    calendar.set_time(hour, minute);
+
+    instant
+}
+
+// The festivals after the date `from` up to and including `to`
+pub fn festivals(
+    calendar: &mut PenAndPaperCalendar,
+    from: (i32, i32, i32),
+    to: (i32, i32, i32),
+) -> Vec<((i32, i32, i32), &'static str)> {
+    let (day, month, year) = calendar.get_date();
+    let (hour, minute) = calendar.get_time();
+    let chronological = |(day, month, year): (i32, i32, i32)| (year, month, day);
+
+    let mut festivals = Vec::new();
+    calendar.set_date(from.0, from.1, from.2);
+    while chronological(calendar.get_date()) < chronological(to) {
+        calendar.advance_time(TimeUnits::Days(1));
+        let date = calendar.get_date();
+        for name in calendar.get_festivals(date.0, date.1, date.2) {
+            festivals.push((date, name));
+        }
+    }
+
+    calendar.set_date(day, month, year);
+    calendar.set_time(hour, minute);
+
+    festivals
+}
+
+// A way of counting years, e.g. after Bosparan's fall. A year of the
+// calendar is `offset` years later in this reckoning.
+#[derive(Copy, Clone)]
+pub struct Reckoning {
+    pub name: &'static str,
+    pub abbreviation: &'static str,
+    pub offset: i32,
+}
+
+impl Reckoning {
+    pub fn from_calendar(&self, year: i32) -> i32 {
+        year + self.offset
+    }
+
+    pub fn to_calendar(&self, year: i32) -> i32 {
+        year - self.offset
+    }
+
+    pub fn format(&self, year: i32) -> String {
+        format!("{} {}", self.from_calendar(year), self.abbreviation)
+    }
+}
+
+// The reckoning dates of the calendar are shown in
+pub fn reckoning(calendar: &PenAndPaperCalendar) -> Reckoning {
+    calendar.reckonings()[calendar.get_reckoning()]
+}
+
 pub trait PenAndPaperCalendar {
     fn get_time(&self) -> (i32, i32);
     fn set_time(&mut self, hour: i32, minute: i32);
@@ -163,6 +613,15 @@ pub trait PenAndPaperCalendar {
 
     fn get_month_name(&self, month: i32) -> &'static str;
 
+    // The calendar counts years in the first reckoning
+    fn reckonings(&self) -> Vec<Reckoning>;
+    fn get_reckoning(&self) -> usize;
+    fn set_reckoning(&mut self, reckoning: usize);
+
+    fn get_weekday_name(&self, day: i32, month: i32, year: i32) -> &'static str;
+    fn get_moon_phase(&self, day: i32, month: i32, year: i32) -> &'static str;
+    fn get_festivals(&self, day: i32, month: i32, year: i32) -> Vec<&'static str>;
+
     fn minutes_per_hour(&mut self) -> i32;
     fn hours_per_day(&mut self) -> i32;
     fn days_per_week(&mut self) -> i32;
@@ -174,6 +633,30 @@ pub trait PenAndPaperCalendar {
     fn evening(&self) -> (i32, i32);
 }
 
+#[derive(Clone)]
+pub struct Condition {
+    pub name: &'static str,
+    pub level: i32,
+    pub source: String,
+}
+
+#[derive(Clone)]
+pub struct Effect {
+    pub name: String,
+    // `None` affects all checks
+    pub target: Option<Stat>,
+    pub value: i32,
+    pub expires: Instant,
+}
+
+pub fn effect_value(p: &Player, target: Option<&Stat>) -> i32 {
+    p.effects()
+        .iter()
+        .filter(|x| x.target.as_ref() == target)
+        .map(|x| x.value)
+        .sum()
+}
+
 pub trait Player {
     fn name(&self) -> &String;
     fn set_name(&mut self, name: String);
@@ -181,16 +664,191 @@ pub trait Player {
     fn get_value(&self, s: &Stat) -> i32;
     fn set_value(&mut self, s: Stat, val: i32);
 
+    // points lost from a pool, the current value is the maximum minus these
+    fn get_spent(&self, s: &Stat) -> i32;
+    fn set_spent(&mut self, s: Stat, val: i32);
+
+    fn conditions(&self) -> &Vec<Condition>;
+    fn conditions_mut(&mut self) -> &mut Vec<Condition>;
+
+    fn effects(&self) -> &Vec<Effect>;
+    fn effects_mut(&mut self) -> &mut Vec<Effect>;
+
     fn get_modifier(&self, s: &String) -> &ModifierValue;
     fn set_modifier(&mut self, s: String, modifier: Box<ModifierValue>);
 }
 
+pub enum Migration {
+    RenameStat(&'static str, &'static str),
+    AddStat(&'static str, i32),
+    RenameModifierValue(&'static str, &'static str, &'static str),
+    // Moves part of a stored stat into a modifier the campaign doesn't have
+    // yet, given as (value, amount of the stat it grants). The value granting
+    // the most the stat covers is chosen, the lowest one if it covers none,
+    // and the stat is reduced by its amount without dropping below 0.
+    MoveStatToModifier(&'static str, &'static str, &'static [(&'static str, i32)]),
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum CheckOutcome {
+    CriticalSuccess,
+    Success,
+    Failure,
+    Botch,
+}
+
+impl CheckOutcome {
+    pub fn is_success(&self) -> bool {
+        match self {
+            CheckOutcome::CriticalSuccess | CheckOutcome::Success => true,
+            CheckOutcome::Failure | CheckOutcome::Botch => false,
+        }
+    }
+}
+
+impl fmt::Display for CheckOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckOutcome::CriticalSuccess => write!(f, "Kritischer Erfolg"),
+            CheckOutcome::Success => write!(f, "Erfolg"),
+            CheckOutcome::Failure => write!(f, "Misserfolg"),
+            CheckOutcome::Botch => write!(f, "Patzer"),
+        }
+    }
+}
+
+pub struct Check {
+    pub name: String,
+    pub rolls: Vec<i32>,
+    pub confirmation: Option<i32>,
+    pub outcome: CheckOutcome,
+    pub details: String,
+}
+
+// Situational modifiers of a check, the chosen value is added to the
+// modifier passed to `PenAndPaperBackend::check`. During combat, choices of
+// per round options are picked by how often the option was used this round.
+pub struct CheckOption {
+    pub name: &'static str,
+    pub choices: Vec<(&'static str, i32)>,
+    pub per_round: bool,
+}
+
+// The experience points of a player and what they were spent on
+pub struct Experience {
+    pub total: i32,
+    pub spent: Vec<(String, i32)>,
+}
+
+impl Experience {
+    pub fn spent_total(&self) -> i32 {
+        self.spent.iter().map(|x| x.1).sum()
+    }
+
+    pub fn available(&self) -> i32 {
+        self.total - self.spent_total()
+    }
+}
+
+pub struct CombatRules {
+    pub initiative: Dice,
+    pub round_seconds: i32,
+}
+
 pub trait PenAndPaperBackend {
+    fn id(&self) -> &'static str;
+
     fn calendar(&mut self) -> &mut PenAndPaperCalendar;
 
     fn add_player(&mut self, name: String) -> &Player;
+    fn player_count(&self) -> usize;
     fn get_player(&mut self, pos: usize) -> &mut Player;
     fn remove_player(&mut self, pos: usize);
 
     fn character_sheet(&mut self) -> CharacterSheet;
+
+    fn combat_rules(&self) -> CombatRules;
+    // Fails if the ruleset lacks the initiative stat
+    fn initiative(&mut self, pos: usize) -> Result<i32, String>;
+
+    fn condition_names(&self) -> Vec<&'static str>;
+    fn max_condition_level(&self) -> i32;
+
+    // `None` if the ruleset doesn't spend experience on stats
+    fn experience(&mut self, pos: usize) -> Option<Experience>;
+
+    // The highest value the player may buy for the stat, `None` if only the
+    // range of the character sheet applies
+    fn maximum(&self, pos: usize, stat: &Stat) -> Option<i32>;
+    // A description of every rule the character breaks
+    fn validate(&mut self, pos: usize) -> Vec<String>;
+
+    // Applies the regeneration of a night's rest
+    fn regenerate(&mut self, pos: usize, rng: &mut DiceRoller) -> Vec<Check>;
+
+    // `None` if the stat can't be checked in this ruleset
+    fn check_options(&self, stat: &Stat) -> Option<Vec<CheckOption>>;
+    fn check(
+        &mut self,
+        pos: usize,
+        stat: &Stat,
+        modifier: i32,
+        rng: &mut DiceRoller,
+    ) -> Option<Check>;
+
+    // Every entry upgrades a campaign by one version, so the current version
+    // is the number of entries
+    fn migrations(&self) -> Vec<Vec<Migration>>;
+}
+
+pub struct BackendFactory {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub version: &'static str,
+
+    create: fn() -> Result<Box<PenAndPaperBackend>, String>,
+}
+
+impl BackendFactory {
+    pub fn new(
+        id: &'static str,
+        name: &'static str,
+        version: &'static str,
+        create: fn() -> Result<Box<PenAndPaperBackend>, String>,
+    ) -> BackendFactory {
+        BackendFactory {
+            id: id,
+            name: name,
+            version: version,
+            create: create,
+        }
+    }
+
+    pub fn create(&self) -> Result<Box<PenAndPaperBackend>, String> {
+        (self.create)()
+    }
+}
+
+pub struct BackendRegistry {
+    factories: Vec<BackendFactory>,
+}
+
+impl BackendRegistry {
+    pub fn new() -> BackendRegistry {
+        BackendRegistry {
+            factories: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, factory: BackendFactory) {
+        self.factories.push(factory);
+    }
+
+    pub fn find(&self, id: &str) -> Option<&BackendFactory> {
+        self.factories.iter().find(|x| x.id == id)
+    }
+
+    pub fn factories(&self) -> &Vec<BackendFactory> {
+        &self.factories
+    }
 }
@@ -0,0 +1,747 @@
+use std::cmp::max;
+use std::fmt;
+use std::fs;
+use std::io;
+
+use chrono::{DateTime, Local};
+use toml::value::{Table, Value};
+
+use crate::application::agenda::*;
+use crate::application::backend::*;
+use crate::application::log::*;
+
+pub const FILE_FILTER: &str = "Kampagne (*.campaign)";
+
+pub enum CampaignError {
+    Io(io::Error),
+    Syntax(String),
+    Invalid(String),
+    Backend(String),
+}
+
+impl fmt::Display for CampaignError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CampaignError::Io(e) => {
+                write!(f, "Auf die Datei konnte nicht zugegriffen werden: {}", e)
+            }
+            CampaignError::Syntax(e) => write!(f, "Die Datei ist keine gültige Kampagne: {}", e),
+            CampaignError::Invalid(e) => write!(f, "Die Kampagne ist beschädigt: {}", e),
+            CampaignError::Backend(e) => {
+                write!(f, "Das Regelwerk konnte nicht erstellt werden: {}", e)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for CampaignError {
+    fn from(e: io::Error) -> CampaignError {
+        CampaignError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for CampaignError {
+    fn from(e: toml::de::Error) -> CampaignError {
+        CampaignError::Syntax(e.to_string())
+    }
+}
+
+impl From<toml::ser::Error> for CampaignError {
+    fn from(e: toml::ser::Error) -> CampaignError {
+        CampaignError::Invalid(e.to_string())
+    }
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Result<&'a Value, CampaignError> {
+    value
+        .get(key)
+        .ok_or_else(|| CampaignError::Invalid(format!("das Feld '{}' fehlt", key)))
+}
+
+fn string<'a>(value: &'a Value, key: &str) -> Result<&'a str, CampaignError> {
+    field(value, key)?
+        .as_str()
+        .ok_or_else(|| CampaignError::Invalid(format!("'{}' muss ein Text sein", key)))
+}
+
+fn integer(value: &Value, key: &str) -> Result<i32, CampaignError> {
+    value
+        .as_integer()
+        .map(|x| x as i32)
+        .ok_or_else(|| CampaignError::Invalid(format!("'{}' muss eine Zahl sein", key)))
+}
+
+fn integers(value: &Value, key: &str, len: usize) -> Result<Vec<i32>, CampaignError> {
+    let values = field(value, key)?
+        .as_array()
+        .filter(|x| x.len() == len)
+        .ok_or_else(|| {
+            CampaignError::Invalid(format!("'{}' muss eine Liste aus {} Zahlen sein", key, len))
+        })?;
+
+    values.iter().map(|x| integer(x, key)).collect()
+}
+
+fn boolean(value: &Value, key: &str) -> Result<bool, CampaignError> {
+    field(value, key)?
+        .as_bool()
+        .ok_or_else(|| CampaignError::Invalid(format!("'{}' muss ein Wahrheitswert sein", key)))
+}
+
+fn integer_array(values: &[i32]) -> Value {
+    Value::Array(values.iter().map(|x| Value::Integer(*x as i64)).collect())
+}
+
+fn migrate_player(player: &mut Value, migration: &Migration) -> Result<(), CampaignError> {
+    let table = player
+        .as_table_mut()
+        .ok_or_else(|| CampaignError::Invalid("ein Spieler muss eine Tabelle sein".to_string()))?;
+
+    match migration {
+        Migration::RenameStat(from, to) => {
+            if let Some(Value::Table(values)) = table.get_mut("values") {
+                if let Some(value) = values.remove(*from) {
+                    values.insert(to.to_string(), value);
+                }
+            }
+        }
+        Migration::AddStat(name, value) => {
+            if let Some(Value::Table(values)) = table.get_mut("values") {
+                if !values.contains_key(*name) {
+                    values.insert(name.to_string(), Value::Integer(*value as i64));
+                }
+            }
+        }
+        Migration::RenameModifierValue(modifier, from, to) => {
+            if let Some(Value::Table(modifiers)) = table.get_mut("modifiers") {
+                if let Some(value) = modifiers.get_mut(*modifier) {
+                    if value.as_str() == Some(*from) {
+                        *value = Value::String(to.to_string());
+                    }
+                }
+            }
+        }
+        Migration::MoveStatToModifier(stat, modifier, choices) => {
+            match table.get("modifiers") {
+                Some(Value::Table(modifiers)) if !modifiers.contains_key(*modifier) => (),
+                _ => return Ok(()),
+            }
+
+            let value = match table.get("values") {
+                Some(Value::Table(values)) => values.get(*stat).and_then(|x| x.as_integer()),
+                _ => None,
+            }
+            .unwrap_or(0) as i32;
+
+            let choice = choices
+                .iter()
+                .filter(|x| x.1 <= value)
+                .max_by_key(|x| x.1)
+                .or_else(|| choices.iter().min_by_key(|x| x.1))
+                .ok_or_else(|| {
+                    CampaignError::Invalid(format!(
+                        "es gibt keinen Wert, in den '{}' verschoben werden kann",
+                        stat
+                    ))
+                })?;
+
+            if let Some(Value::Table(values)) = table.get_mut("values") {
+                let remaining = max(value - choice.1, 0);
+                values.insert(stat.to_string(), Value::Integer(remaining as i64));
+            }
+            if let Some(Value::Table(modifiers)) = table.get_mut("modifiers") {
+                modifiers.insert(modifier.to_string(), Value::String(choice.0.to_string()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn migrate(backend: &PenAndPaperBackend, campaign: &mut Value) -> Result<(), CampaignError> {
+    let migrations = backend.migrations();
+
+    let version = match campaign.get("version") {
+        Some(version) => integer(version, "version")? as usize,
+        None => 0,
+    };
+
+    if version > migrations.len() {
+        return Err(CampaignError::Invalid(format!(
+            "die Version {} ist neuer als die unterstützte Version {}",
+            version,
+            migrations.len()
+        )));
+    }
+
+    if let Some(Value::Array(players)) = campaign.get_mut("players") {
+        for player in players {
+            for migration in migrations[version..].iter().flatten() {
+                migrate_player(player, migration)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn save_calendar(calendar: &PenAndPaperCalendar) -> Value {
+    let (day, month, year) = calendar.get_date();
+    let (hour, minute) = calendar.get_time();
+
+    let mut table = Table::new();
+    table.insert("date".to_string(), integer_array(&[day, month, year]));
+    table.insert("time".to_string(), integer_array(&[hour, minute]));
+    table.insert(
+        "reckoning".to_string(),
+        Value::String(reckoning(calendar).abbreviation.to_string()),
+    );
+
+    Value::Table(table)
+}
+
+fn load_calendar(calendar: &mut PenAndPaperCalendar, value: &Value) -> Result<(), CampaignError> {
+    let date = integers(value, "date", 3)?;
+    let time = integers(value, "time", 2)?;
+
+    calendar.set_date(date[0], date[1], date[2]);
+    calendar.set_time(time[0], time[1]);
+
+    // campaigns saved before reckonings existed use the calendar's own
+    if value.get("reckoning").is_some() {
+        let abbreviation = string(value, "reckoning")?;
+        let index = calendar
+            .reckonings()
+            .iter()
+            .position(|x| x.abbreviation == abbreviation)
+            .ok_or_else(|| {
+                CampaignError::Invalid(format!("unbekannte Zeitrechnung '{}'", abbreviation))
+            })?;
+        calendar.set_reckoning(index);
+    }
+
+    Ok(())
+}
+
+fn save_player(sheet: &CharacterSheet, player: &Player) -> Value {
+    let mut modifiers = Table::new();
+    let mut values = Table::new();
+    let mut spent = Table::new();
+
+    for category in sheet.categories() {
+        for entry in &category.entries {
+            match entry {
+                CategoryEntry::Modifier(modifier) => {
+                    let value = player.get_modifier(&modifier.name.to_string()).name();
+                    modifiers.insert(modifier.name.to_string(), Value::String(value));
+                }
+                CategoryEntry::Stat(stat) => {
+                    if stat.pool {
+                        let value = player.get_spent(&stat.stat) as i64;
+                        spent.insert(stat.stat.identifier().to_string(), Value::Integer(value));
+                    }
+
+                    match stat.stat {
+                        Stat::Calculated(_) => (),
+                        _ => {
+                            let value = player.get_value(&stat.stat) as i64;
+                            values
+                                .insert(stat.stat.identifier().to_string(), Value::Integer(value));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut table = Table::new();
+    table.insert("name".to_string(), Value::String(player.name().to_string()));
+    table.insert("modifiers".to_string(), Value::Table(modifiers));
+    table.insert("values".to_string(), Value::Table(values));
+    table.insert("spent".to_string(), Value::Table(spent));
+    table.insert(
+        "effects".to_string(),
+        Value::Array(
+            player
+                .effects()
+                .iter()
+                .map(|x| {
+                    let (year, month, day, hour, minute) = x.expires;
+
+                    let mut effect = Table::new();
+                    effect.insert("name".to_string(), Value::String(x.name.clone()));
+                    if let Some(target) = &x.target {
+                        effect.insert(
+                            "target".to_string(),
+                            Value::String(target.identifier().to_string()),
+                        );
+                    }
+                    effect.insert("value".to_string(), Value::Integer(x.value as i64));
+                    effect.insert(
+                        "expires".to_string(),
+                        integer_array(&[year, month, day, hour, minute]),
+                    );
+                    Value::Table(effect)
+                })
+                .collect(),
+        ),
+    );
+    table.insert(
+        "conditions".to_string(),
+        Value::Array(
+            player
+                .conditions()
+                .iter()
+                .map(|x| {
+                    let mut condition = Table::new();
+                    condition.insert("name".to_string(), Value::String(x.name.to_string()));
+                    condition.insert("level".to_string(), Value::Integer(x.level as i64));
+                    condition.insert("source".to_string(), Value::String(x.source.clone()));
+                    Value::Table(condition)
+                })
+                .collect(),
+        ),
+    );
+
+    Value::Table(table)
+}
+
+fn load_player(
+    backend: &mut PenAndPaperBackend,
+    sheet: &CharacterSheet,
+    value: &Value,
+) -> Result<(), CampaignError> {
+    let names = backend.condition_names();
+    backend.add_player(string(value, "name")?.to_string());
+    let index = backend.player_count() - 1;
+    let player = backend.get_player(index);
+
+    // modifiers have to be restored in sheet order, as the available values
+    // of later modifiers may depend on earlier ones (e.g. culture on race)
+    let modifiers = field(value, "modifiers")?;
+    for category in sheet.categories() {
+        for entry in &category.entries {
+            if let CategoryEntry::Modifier(modifier) = entry {
+                let name = match modifiers.get(modifier.name) {
+                    Some(name) => name.as_str().ok_or_else(|| {
+                        CampaignError::Invalid(format!("'{}' muss ein Text sein", modifier.name))
+                    })?,
+                    None => continue,
+                };
+
+                let mut values = modifier.get_values(player);
+                let index = values
+                    .iter()
+                    .position(|x| x.name() == name)
+                    .ok_or_else(|| {
+                        CampaignError::Invalid(format!(
+                            "unbekannter Wert '{}' für '{}'",
+                            name, modifier.name
+                        ))
+                    })?;
+                player.set_modifier(modifier.name.to_string(), values.swap_remove(index));
+            }
+        }
+    }
+
+    let values = field(value, "values")?
+        .as_table()
+        .ok_or_else(|| CampaignError::Invalid("'values' muss eine Tabelle sein".to_string()))?;
+    for (key, value) in values {
+        if let Some(stat) = sheet.find_stat(key) {
+            player.set_value(stat.stat.clone(), integer(value, key)?);
+        }
+    }
+
+    if let Some(effects) = value.get("effects") {
+        let effects = effects
+            .as_array()
+            .ok_or_else(|| CampaignError::Invalid("'effects' muss eine Liste sein".to_string()))?;
+        for effect in effects {
+            // effects on stats the ruleset no longer has are dropped
+            let target = match effect.get("target") {
+                Some(_) => match sheet.find_stat(&string(effect, "target")?.to_string()) {
+                    Some(stat) => Some(stat.stat.clone()),
+                    None => continue,
+                },
+                None => None,
+            };
+            let expires = integers(effect, "expires", 5)?;
+
+            player.effects_mut().push(Effect {
+                name: string(effect, "name")?.to_string(),
+                target: target,
+                value: integer(field(effect, "value")?, "value")?,
+                expires: (expires[0], expires[1], expires[2], expires[3], expires[4]),
+            });
+        }
+    }
+
+    if let Some(conditions) = value.get("conditions") {
+        let conditions = conditions.as_array().ok_or_else(|| {
+            CampaignError::Invalid("'conditions' muss eine Liste sein".to_string())
+        })?;
+        for condition in conditions {
+            let name = string(condition, "name")?;
+            let name = names
+                .iter()
+                .find(|x| **x == name)
+                .ok_or_else(|| CampaignError::Invalid(format!("unbekannter Zustand '{}'", name)))?;
+
+            player.conditions_mut().push(Condition {
+                name: name,
+                level: integer(field(condition, "level")?, "level")?,
+                source: string(condition, "source")?.to_string(),
+            });
+        }
+    }
+
+    // campaigns saved before pools were tracked have nothing spent
+    if let Some(spent) = value.get("spent") {
+        let spent = spent
+            .as_table()
+            .ok_or_else(|| CampaignError::Invalid("'spent' muss eine Tabelle sein".to_string()))?;
+        for (key, value) in spent {
+            if let Some(stat) = sheet.find_stat(key) {
+                player.set_spent(stat.stat.clone(), integer(value, key)?);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+const OUTCOMES: [(&str, CheckOutcome); 4] = [
+    ("critical", CheckOutcome::CriticalSuccess),
+    ("success", CheckOutcome::Success),
+    ("failure", CheckOutcome::Failure),
+    ("botch", CheckOutcome::Botch),
+];
+
+fn save_log_entry(entry: &LogEntry) -> Value {
+    let (day, month, year) = entry.date;
+    let (hour, minute) = entry.time;
+    let outcome = OUTCOMES
+        .iter()
+        .find(|x| x.1 == entry.check.outcome)
+        .unwrap()
+        .0;
+
+    let mut table = Table::new();
+    table.insert("date".to_string(), integer_array(&[day, month, year]));
+    table.insert("time".to_string(), integer_array(&[hour, minute]));
+    table.insert(
+        "timestamp".to_string(),
+        Value::String(entry.timestamp.to_rfc3339()),
+    );
+    table.insert("player".to_string(), Value::String(entry.player.clone()));
+    table.insert("check".to_string(), Value::String(entry.check.name.clone()));
+    table.insert("rolls".to_string(), integer_array(&entry.check.rolls));
+    if let Some(confirmation) = entry.check.confirmation {
+        table.insert(
+            "confirmation".to_string(),
+            Value::Integer(confirmation as i64),
+        );
+    }
+    table.insert("outcome".to_string(), Value::String(outcome.to_string()));
+    table.insert(
+        "details".to_string(),
+        Value::String(entry.check.details.clone()),
+    );
+    table.insert("hidden".to_string(), Value::Boolean(entry.hidden));
+
+    Value::Table(table)
+}
+
+fn load_log_entry(value: &Value) -> Result<LogEntry, CampaignError> {
+    let date = integers(value, "date", 3)?;
+    let time = integers(value, "time", 2)?;
+    let timestamp = DateTime::parse_from_rfc3339(string(value, "timestamp")?)
+        .map_err(|e| CampaignError::Invalid(format!("ungültiger Zeitstempel: {}", e)))?;
+    let rolls = field(value, "rolls")?
+        .as_array()
+        .ok_or_else(|| CampaignError::Invalid("'rolls' muss eine Liste sein".to_string()))?
+        .iter()
+        .map(|x| integer(x, "rolls"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let outcome = string(value, "outcome")?;
+
+    Ok(LogEntry {
+        date: (date[0], date[1], date[2]),
+        time: (time[0], time[1]),
+        timestamp: timestamp.with_timezone(&Local),
+        player: string(value, "player")?.to_string(),
+        check: Check {
+            name: string(value, "check")?.to_string(),
+            rolls: rolls,
+            confirmation: match value.get("confirmation") {
+                Some(x) => Some(integer(x, "confirmation")?),
+                None => None,
+            },
+            outcome: OUTCOMES
+                .iter()
+                .find(|x| x.0 == outcome)
+                .map(|x| x.1)
+                .ok_or_else(|| {
+                    CampaignError::Invalid(format!("unbekanntes Ergebnis '{}'", outcome))
+                })?,
+            details: string(value, "details")?.to_string(),
+        },
+        hidden: boolean(value, "hidden")?,
+    })
+}
+
+fn save_event(event: &Event) -> Value {
+    let (year, month, day, hour, minute) = event.at;
+
+    let mut table = Table::new();
+    table.insert("name".to_string(), Value::String(event.name.clone()));
+    table.insert(
+        "at".to_string(),
+        integer_array(&[year, month, day, hour, minute]),
+    );
+
+    Value::Table(table)
+}
+
+fn load_event(value: &Value) -> Result<Event, CampaignError> {
+    let at = integers(value, "at", 5)?;
+
+    Ok(Event {
+        name: string(value, "name")?.to_string(),
+        at: (at[0], at[1], at[2], at[3], at[4]),
+    })
+}
+
+pub fn save(
+    backend: &mut PenAndPaperBackend,
+    log: &RollLog,
+    agenda: &Agenda,
+    path: &str,
+) -> Result<(), CampaignError> {
+    let sheet = backend.character_sheet();
+
+    let players = (0..backend.player_count())
+        .map(|i| save_player(&sheet, backend.get_player(i)))
+        .collect();
+
+    let mut campaign = Table::new();
+    campaign.insert(
+        "backend".to_string(),
+        Value::String(backend.id().to_string()),
+    );
+    campaign.insert(
+        "version".to_string(),
+        Value::Integer(backend.migrations().len() as i64),
+    );
+    campaign.insert("calendar".to_string(), save_calendar(backend.calendar()));
+    campaign.insert("players".to_string(), Value::Array(players));
+    campaign.insert(
+        "log".to_string(),
+        Value::Array(log.entries().iter().map(save_log_entry).collect()),
+    );
+    campaign.insert(
+        "events".to_string(),
+        Value::Array(agenda.events().iter().map(save_event).collect()),
+    );
+
+    fs::write(path, toml::to_string(&Value::Table(campaign))?)?;
+
+    Ok(())
+}
+
+pub fn load(
+    registry: &BackendRegistry,
+    path: &str,
+) -> Result<(Box<PenAndPaperBackend>, RollLog, Agenda), CampaignError> {
+    let mut campaign: Value = fs::read_to_string(path)?.parse()?;
+
+    // campaigns saved before backends were selectable are always DSA
+    let id = match campaign.get("backend") {
+        Some(_) => string(&campaign, "backend")?,
+        None => "dsa5",
+    };
+    let mut backend = registry
+        .find(id)
+        .ok_or_else(|| CampaignError::Invalid(format!("unbekanntes Regelwerk '{}'", id)))?
+        .create()
+        .map_err(CampaignError::Backend)?;
+
+    migrate(backend.as_ref(), &mut campaign)?;
+
+    load_calendar(backend.calendar(), field(&campaign, "calendar")?)?;
+
+    let sheet = backend.character_sheet();
+    let players = field(&campaign, "players")?
+        .as_array()
+        .ok_or_else(|| CampaignError::Invalid("'players' muss eine Liste sein".to_string()))?;
+    for player in players {
+        load_player(backend.as_mut(), &sheet, player)?;
+    }
+
+    // campaigns saved before the roll log existed have none
+    let mut log = RollLog::new();
+    if let Some(entries) = campaign.get("log") {
+        let entries = entries
+            .as_array()
+            .ok_or_else(|| CampaignError::Invalid("'log' muss eine Liste sein".to_string()))?;
+        for entry in entries {
+            log.add(load_log_entry(entry)?);
+        }
+    }
+
+    let mut agenda = Agenda::new();
+    if let Some(events) = campaign.get("events") {
+        let events = events
+            .as_array()
+            .ok_or_else(|| CampaignError::Invalid("'events' muss eine Liste sein".to_string()))?;
+        for event in events {
+            agenda.add(load_event(event)?);
+        }
+    }
+
+    Ok((backend, log, agenda))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::dsa::DSABackend;
+
+    fn migrated(campaign: &str) -> Result<Value, String> {
+        let backend = DSABackend::new().ok().unwrap();
+        let mut campaign: Value = campaign.parse().ok().unwrap();
+        migrate(&backend, &mut campaign).map_err(|e| e.to_string())?;
+        Ok(campaign["players"][0].clone())
+    }
+
+    #[test]
+    fn migrates_from_the_first_version() {
+        let player = migrated(
+            r#"
+            [[players]]
+            name = "Alrik"
+            values = { Schleichen = 4, "(Ent-)Fesseln" = 2, AP = 1150 }
+            modifiers = { Kultur = "Suedaventurier (26 AP)" }
+            "#,
+        )
+        .ok()
+        .unwrap();
+
+        assert_eq!(player["values"].get("Schleichen"), None);
+        assert_eq!(player["values"]["Verbergen"].as_integer(), Some(4));
+        assert_eq!(player["values"]["Fesseln"].as_integer(), Some(2));
+        assert_eq!(player["values"]["AP"].as_integer(), Some(50));
+        assert_eq!(
+            player["modifiers"]["Kultur"].as_str(),
+            Some("Südaventurier (26 AP)")
+        );
+        assert_eq!(
+            player["modifiers"]["Erfahrungsgrad"].as_str(),
+            Some("Erfahren (1100 AP)")
+        );
+    }
+
+    #[test]
+    fn keeps_an_existing_experience_level() {
+        let player = migrated(
+            r#"
+            version = 2
+            [[players]]
+            name = "Alrik"
+            values = { AP = 1100 }
+            modifiers = { Erfahrungsgrad = "Erfahren (1100 AP)" }
+            "#,
+        )
+        .ok()
+        .unwrap();
+
+        assert_eq!(player["values"]["AP"].as_integer(), Some(1100));
+        assert_eq!(
+            player["modifiers"]["Erfahrungsgrad"].as_str(),
+            Some("Erfahren (1100 AP)")
+        );
+    }
+
+    #[test]
+    fn moves_the_starting_ap_into_the_experience_level() {
+        let campaign = |ap: i32| {
+            migrated(&format!(
+                "version = 2\n[[players]]\nname = \"Alrik\"\nvalues = {{ AP = {} }}\nmodifiers = {{}}",
+                ap
+            ))
+            .ok()
+            .unwrap()
+        };
+
+        let player = campaign(1000);
+        assert_eq!(player["values"]["AP"].as_integer(), Some(0));
+        assert_eq!(
+            player["modifiers"]["Erfahrungsgrad"].as_str(),
+            Some("Durchschnittlich (1000 AP)")
+        );
+
+        let player = campaign(1450);
+        assert_eq!(player["values"]["AP"].as_integer(), Some(50));
+        assert_eq!(
+            player["modifiers"]["Erfahrungsgrad"].as_str(),
+            Some("Meisterlich (1400 AP)")
+        );
+
+        // below every level the AP don't become negative
+        let player = campaign(300);
+        assert_eq!(player["values"]["AP"].as_integer(), Some(0));
+        assert_eq!(
+            player["modifiers"]["Erfahrungsgrad"].as_str(),
+            Some("Unerfahren (900 AP)")
+        );
+    }
+
+    #[test]
+    fn skips_applied_migrations() {
+        let player = migrated(
+            r#"
+            version = 4
+            [[players]]
+            name = "Alrik"
+            values = { Schleichen = 4, AP = 1150 }
+            modifiers = {}
+            "#,
+        )
+        .ok()
+        .unwrap();
+
+        assert_eq!(player["values"]["Schleichen"].as_integer(), Some(4));
+        assert_eq!(player["values"]["AP"].as_integer(), Some(1150));
+        assert_eq!(player["modifiers"].get("Erfahrungsgrad"), None);
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let error = migrated("version = 5\nplayers = []").err().unwrap();
+        assert_eq!(
+            error,
+            "Die Kampagne ist beschädigt: die Version 5 ist neuer als die unterstützte Version 4"
+        );
+    }
+
+    #[test]
+    fn renames_modifier_values() {
+        let player = migrated(
+            r#"
+            version = 3
+            [[players]]
+            name = "Alrik"
+            values = {}
+            modifiers = { Kultur = "Thorwaller (24 AP)", Erfahrungsgrad = "Erfahren (1100 AP)" }
+            "#,
+        )
+        .ok()
+        .unwrap();
+
+        assert_eq!(
+            player["modifiers"]["Kultur"].as_str(),
+            Some("Thorwaler (24 AP)")
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use crate::application::dice::*;
+
+pub struct Combatant {
+    pub name: String,
+    // index of the backend player, `None` for NPCs
+    pub player: Option<usize>,
+    pub base: i32,
+    pub initiative: i32,
+
+    actions: HashMap<&'static str, usize>,
+}
+
+impl Combatant {
+    pub fn actions(&self, action: &str) -> usize {
+        self.actions.get(action).cloned().unwrap_or(0)
+    }
+
+    pub fn use_action(&mut self, action: &'static str) {
+        *self.actions.entry(action).or_insert(0) += 1;
+    }
+}
+
+pub struct Combat {
+    combatants: Vec<Combatant>,
+    current: usize,
+    round: i32,
+
+    dice: Dice,
+    round_seconds: i32,
+    seconds: i32,
+}
+
+impl Combat {
+    pub fn new(dice: Dice, round_seconds: i32) -> Combat {
+        Combat {
+            combatants: Vec::new(),
+            current: 0,
+            round: 1,
+            dice: dice,
+            round_seconds: round_seconds,
+            seconds: 0,
+        }
+    }
+
+    // Rolls the initiative and keeps the turn order sorted. Ties go to the
+    // higher base value.
+    pub fn add(&mut self, name: String, player: Option<usize>, base: i32, rng: &mut DiceRoller) {
+        let combatant = Combatant {
+            name: name,
+            player: player,
+            base: base,
+            initiative: base + self.dice.roll(rng).total,
+            actions: HashMap::new(),
+        };
+
+        let index = self
+            .combatants
+            .iter()
+            .position(|x| (x.initiative, x.base) < (combatant.initiative, combatant.base))
+            .unwrap_or(self.combatants.len());
+
+        // at the start of a round the fastest combatant acts first
+        if index <= self.current && self.current > 0 {
+            self.current += 1;
+        }
+        self.combatants.insert(index, combatant);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.combatants.remove(index);
+
+        if index < self.current {
+            self.current -= 1;
+        }
+        if self.current >= self.combatants.len() {
+            self.current = 0;
+        }
+    }
+
+    // Backend players after a removed one move up by one
+    pub fn remove_player(&mut self, player: usize) {
+        if let Some(index) = self
+            .combatants
+            .iter()
+            .position(|x| x.player == Some(player))
+        {
+            self.remove(index);
+        }
+
+        for combatant in &mut self.combatants {
+            match combatant.player {
+                Some(x) if x > player => combatant.player = Some(x - 1),
+                _ => (),
+            }
+        }
+    }
+
+    pub fn combatants(&self) -> &Vec<Combatant> {
+        &self.combatants
+    }
+
+    pub fn find_player(&mut self, player: usize) -> Option<&mut Combatant> {
+        self.combatants
+            .iter_mut()
+            .find(|x| x.player == Some(player))
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn round(&self) -> i32 {
+        self.round
+    }
+
+    // Returns the minutes that passed if a new round started
+    pub fn next_turn(&mut self) -> Option<i32> {
+        self.current += 1;
+        if self.current < self.combatants.len() {
+            return None;
+        }
+
+        self.current = 0;
+        self.round += 1;
+        for combatant in &mut self.combatants {
+            combatant.actions.clear();
+        }
+
+        self.seconds += self.round_seconds;
+        let minutes = self.seconds / 60;
+        self.seconds %= 60;
+
+        Some(minutes)
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::Rng;
+
+pub trait DiceRoller {
+    fn roll_die(&mut self, sides: i32) -> i32;
+}
+
+impl<R: Rng> DiceRoller for R {
+    fn roll_die(&mut self, sides: i32) -> i32 {
+        self.gen_range(1, sides + 1)
+    }
+}
+
+// Keeps rolls and their totals well within i32
+const MAX_COUNT: i32 = 100;
+const MAX_SIDES: i32 = 1000;
+const MAX_MODIFIER: i32 = 1000;
+
+pub struct DiceError(String);
+
+impl fmt::Display for DiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid dice expression: {}", self.0)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct Dice {
+    pub count: i32,
+    pub sides: i32,
+    pub modifier: i32,
+}
+
+impl Dice {
+    pub fn new(count: i32, sides: i32, modifier: i32) -> Dice {
+        Dice {
+            count: count,
+            sides: sides,
+            modifier: modifier,
+        }
+    }
+
+    pub fn roll(&self, rng: &mut DiceRoller) -> Roll {
+        let results: Vec<i32> = (0..self.count).map(|_| rng.roll_die(self.sides)).collect();
+
+        Roll {
+            dice: *self,
+            total: results.iter().sum::<i32>() + self.modifier,
+            results: results,
+        }
+    }
+}
+
+// Accepts the German (3W20, W6+2) as well as the English (2d6-1) notation
+impl FromStr for Dice {
+    type Err = DiceError;
+
+    fn from_str(s: &str) -> Result<Dice, DiceError> {
+        let expression: String = s.chars().filter(|x| !x.is_whitespace()).collect();
+        let error = || DiceError(s.to_string());
+
+        let separator = expression.find(|x| "WwDd".contains(x)).ok_or_else(error)?;
+        let (count, rest) = expression.split_at(separator);
+        let rest = &rest[1..];
+
+        let count = match count {
+            "" => 1,
+            x => x.parse().map_err(|_| error())?,
+        };
+
+        let (sides, modifier) = match rest.find(|x| x == '+' || x == '-') {
+            Some(index) => rest.split_at(index),
+            None => (rest, ""),
+        };
+
+        let sides = sides.parse().map_err(|_| error())?;
+        let modifier = match modifier {
+            "" => 0,
+            x => x.trim_start_matches('+').parse().map_err(|_| error())?,
+        };
+
+        if count < 1 || count > MAX_COUNT || sides < 1 || sides > MAX_SIDES {
+            return Err(error());
+        }
+        if modifier < -MAX_MODIFIER || modifier > MAX_MODIFIER {
+            return Err(error());
+        }
+
+        Ok(Dice::new(count, sides, modifier))
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}W{}", self.count, self.sides)?;
+        match self.modifier {
+            0 => Ok(()),
+            x if x > 0 => write!(f, "+{}", x),
+            x => write!(f, "{}", x),
+        }
+    }
+}
+
+pub struct Roll {
+    pub dice: Dice,
+    pub results: Vec<i32>,
+    pub total: i32,
+}
+
+impl fmt::Display for Roll {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let results: Vec<String> = self.results.iter().map(|x| x.to_string()).collect();
+        write!(
+            f,
+            "{}: [{}] = {}",
+            self.dice,
+            results.join(", "),
+            self.total
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<(i32, i32, i32)> {
+        s.parse::<Dice>()
+            .ok()
+            .map(|x| (x.count, x.sides, x.modifier))
+    }
+
+    #[test]
+    fn parses_german_and_english_notation() {
+        assert_eq!(parse("W20"), Some((1, 20, 0)));
+        assert_eq!(parse("3W20"), Some((3, 20, 0)));
+        assert_eq!(parse("2d6-1"), Some((2, 6, -1)));
+        assert_eq!(parse("1W6 + 4"), Some((1, 6, 4)));
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert_eq!(parse("0W6"), None);
+        assert_eq!(parse("W6+"), None);
+        assert_eq!(parse("2W"), None);
+        assert_eq!(parse("20"), None);
+    }
+
+    #[test]
+    fn rejects_too_many_dice_and_sides() {
+        assert_eq!(parse("100W1000+1000"), Some((100, 1000, 1000)));
+        assert_eq!(parse("1W2147483647"), None);
+        assert_eq!(parse("2000000000W6"), None);
+        assert_eq!(parse("101W6"), None);
+        assert_eq!(parse("1W1001"), None);
+        assert_eq!(parse("1W6-2147483648"), None);
+    }
+
+    #[test]
+    fn formats_german_notation() {
+        assert_eq!(Dice::new(1, 6, 0).to_string(), "1W6");
+        assert_eq!(Dice::new(2, 6, -1).to_string(), "2W6-1");
+        assert_eq!(Dice::new(3, 20, 2).to_string(), "3W20+2");
+    }
+}
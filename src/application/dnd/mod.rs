@@ -0,0 +1,545 @@
+use std::cmp::{max, min};
+use std::collections::HashMap;
+
+use crate::application::backend::*;
+use crate::application::dice::{Dice, DiceRoller};
+use crate::application::ruleset::*;
+
+const ID: &str = "dnd5e";
+const RULESET: &str = "rulesets/dnd.toml";
+
+// Festivals are modeled as months of their own, so every day of the year
+// belongs to exactly one month
+const MONTHS: [(&str, i32); 17] = [
+    ("Hammer", 30),
+    ("Midwinter", 1),
+    ("Alturiak", 30),
+    ("Ches", 30),
+    ("Tarsakh", 30),
+    ("Greengrass", 1),
+    ("Mirtul", 30),
+    ("Kythorn", 30),
+    ("Flamerule", 30),
+    ("Midsummer", 1),
+    ("Eleasis", 30),
+    ("Eleint", 30),
+    ("Highharvestide", 1),
+    ("Marpenoth", 30),
+    ("Uktar", 30),
+    ("Feast of the Moon", 1),
+    ("Nightal", 30),
+];
+
+const MIDSUMMER: i32 = 10;
+
+const SOLSTICES: [(i32, i32, &str); 4] = [
+    (19, 4, "Spring Equinox"),
+    (20, 8, "Summer Solstice"),
+    (21, 12, "Autumn Equinox"),
+    (20, 17, "Winter Solstice"),
+];
+
+const TENDAY: [&str; 10] = [
+    "First-day",
+    "Second-day",
+    "Third-day",
+    "Fourth-day",
+    "Fifth-day",
+    "Sixth-day",
+    "Seventh-day",
+    "Eighth-day",
+    "Ninth-day",
+    "Tenth-day",
+];
+
+// Selûne was full on Hammer 1, 1372 DR and waxes and wanes in 30 days and
+// 10.5 hours
+const SELUNE_FULL_MOON: i32 = 1372;
+const SELUNE_CYCLE: f64 = 30.4375;
+const SELUNE_PHASES: [&str; 8] = [
+    "Selûne: New Moon",
+    "Selûne: Waxing Crescent",
+    "Selûne: First Quarter",
+    "Selûne: Waxing Gibbous",
+    "Selûne: Full Moon",
+    "Selûne: Waning Gibbous",
+    "Selûne: Last Quarter",
+    "Selûne: Waning Crescent",
+];
+
+pub struct HarptosCalendar {
+    hour: i32,
+    minute: i32,
+
+    day: i32,
+    month: i32,
+    year: i32,
+}
+
+impl HarptosCalendar {
+    // Shieldmeet follows Midsummer every fourth year
+    fn length(month: i32, year: i32) -> i32 {
+        match month {
+            MIDSUMMER if year % 4 == 0 => 2,
+            x => MONTHS[(x - 1) as usize].1,
+        }
+    }
+
+    fn month_length(&self, month: i32) -> i32 {
+        HarptosCalendar::length(month, self.year)
+    }
+
+    // Days since Hammer 1, 0 DR
+    fn days(day: i32, month: i32, year: i32) -> i32 {
+        let leap_days = (year + 3).div_euclid(4);
+        let months: i32 = (1..month).map(|x| HarptosCalendar::length(x, year)).sum();
+        365 * year + leap_days + months + day - 1
+    }
+
+    fn is_festival(month: i32) -> bool {
+        MONTHS[(month - 1) as usize].1 == 1
+    }
+
+    fn next_month(&mut self) {
+        self.month += 1;
+        if self.month > MONTHS.len() as i32 {
+            self.month = 1;
+            self.year += 1;
+        }
+    }
+
+    fn prev_month(&mut self) {
+        self.month -= 1;
+        if self.month < 1 {
+            self.month = MONTHS.len() as i32;
+            self.year -= 1;
+        }
+    }
+}
+
+impl PenAndPaperCalendar for HarptosCalendar {
+    fn get_time(&self) -> (i32, i32) {
+        (self.hour, self.minute)
+    }
+
+    fn set_time(&mut self, hour: i32, minute: i32) {
+        self.hour = min(max(hour, 0), 23);
+        self.minute = min(max(minute, 0), 59);
+    }
+
+    fn get_date(&self) -> (i32, i32, i32) {
+        (self.day, self.month, self.year)
+    }
+
+    fn set_date(&mut self, day: i32, month: i32, year: i32) {
+        self.year = year;
+        self.month = min(max(month, 1), MONTHS.len() as i32);
+        self.day = min(max(day, 1), self.month_length(self.month));
+    }
+
+    fn advance_time(&mut self, t: TimeUnits) {
+        match t {
+            TimeUnits::Minutes(m) => {
+                let minutes = self.minute + m;
+                let mut hours = minutes / 60;
+                self.minute = minutes % 60;
+                if self.minute < 0 {
+                    hours -= 1;
+                    self.minute += 60;
+                }
+                self.advance_time(TimeUnits::Hours(hours));
+            }
+            TimeUnits::Hours(h) => {
+                let hours = self.hour + h;
+                let mut days = hours / 24;
+                self.hour = hours % 24;
+                if self.hour < 0 {
+                    days -= 1;
+                    self.hour += 24;
+                }
+                self.advance_time(TimeUnits::Days(days));
+            }
+            TimeUnits::Days(d) => {
+                let mut day = self.day + d;
+                while day > self.month_length(self.month) {
+                    day -= self.month_length(self.month);
+                    self.next_month();
+                }
+                while day < 1 {
+                    self.prev_month();
+                    day += self.month_length(self.month);
+                }
+                self.day = day;
+            }
+            TimeUnits::Weeks(w) => {
+                self.advance_time(TimeUnits::Days(10 * w));
+            }
+            TimeUnits::Months(m) => {
+                // months skip the festivals and keep the day of the month
+                for _ in 0..m.abs() {
+                    loop {
+                        if m > 0 {
+                            self.next_month()
+                        } else {
+                            self.prev_month()
+                        }
+
+                        if !HarptosCalendar::is_festival(self.month) {
+                            break;
+                        }
+                    }
+                }
+                self.day = min(self.day, self.month_length(self.month));
+            }
+            TimeUnits::Years(y) => {
+                self.year += y;
+                self.day = min(self.day, self.month_length(self.month));
+            }
+        }
+    }
+
+    fn get_month_name(&self, month: i32) -> &'static str {
+        match month {
+            x if x >= 1 && x <= MONTHS.len() as i32 => MONTHS[(x - 1) as usize].0,
+            _ => "Undefined",
+        }
+    }
+
+    fn get_festivals(&self, day: i32, month: i32, _: i32) -> Vec<&'static str> {
+        let mut festivals: Vec<&'static str> = SOLSTICES
+            .iter()
+            .filter(|x| x.0 == day && x.1 == month)
+            .map(|x| x.2)
+            .collect();
+        match month {
+            MIDSUMMER if day == 2 => festivals.push("Shieldmeet"),
+            x if HarptosCalendar::is_festival(x) => festivals.push(MONTHS[(x - 1) as usize].0),
+            _ => (),
+        }
+        festivals
+    }
+
+    fn reckonings(&self) -> Vec<Reckoning> {
+        vec![Reckoning {
+            name: "Dalereckoning",
+            abbreviation: "DR",
+            offset: 0,
+        }]
+    }
+
+    fn get_reckoning(&self) -> usize {
+        0
+    }
+
+    fn set_reckoning(&mut self, _: usize) {}
+
+    // The days of a tenday have no names, festivals belong to no tenday
+    fn get_weekday_name(&self, day: i32, month: i32, _: i32) -> &'static str {
+        match HarptosCalendar::is_festival(month) {
+            true => "",
+            false => TENDAY[((day - 1) % 10) as usize],
+        }
+    }
+
+    fn get_moon_phase(&self, day: i32, month: i32, year: i32) -> &'static str {
+        let full_moon = HarptosCalendar::days(1, 1, SELUNE_FULL_MOON) as f64;
+        let age = (HarptosCalendar::days(day, month, year) as f64 - full_moon + SELUNE_CYCLE / 2.0)
+            .rem_euclid(SELUNE_CYCLE);
+        SELUNE_PHASES[((age / SELUNE_CYCLE * 8.0).round() as usize) % 8]
+    }
+
+    fn minutes_per_hour(&mut self) -> i32 {
+        60
+    }
+    fn hours_per_day(&mut self) -> i32 {
+        24
+    }
+    fn days_per_week(&mut self) -> i32 {
+        10
+    }
+    fn days_per_month(&mut self, month: i32) -> i32 {
+        self.month_length(month)
+    }
+    fn months_per_year(&mut self) -> i32 {
+        MONTHS.len() as i32
+    }
+
+    fn morning(&self) -> (i32, i32) {
+        (07, 00)
+    }
+    fn noon(&self) -> (i32, i32) {
+        (12, 00)
+    }
+    fn evening(&self) -> (i32, i32) {
+        (19, 00)
+    }
+}
+
+pub struct DNDPlayer {
+    _name: String,
+
+    character_sheet: HashMap<Stat, i32>,
+    spent: HashMap<Stat, i32>,
+    conditions: Vec<Condition>,
+    effects: Vec<Effect>,
+    modifiers: HashMap<String, Box<ModifierValue>>,
+}
+
+impl Player for DNDPlayer {
+    fn name(&self) -> &String {
+        &self._name
+    }
+
+    fn set_name(&mut self, name: String) {
+        self._name = name
+    }
+
+    fn get_value(&self, s: &Stat) -> i32 {
+        if self.character_sheet.contains_key(s) {
+            self.character_sheet[s]
+        } else {
+            0
+        }
+    }
+
+    fn set_value(&mut self, s: Stat, val: i32) {
+        self.character_sheet.insert(s, val);
+    }
+
+    fn get_spent(&self, s: &Stat) -> i32 {
+        self.spent.get(s).cloned().unwrap_or(0)
+    }
+
+    fn set_spent(&mut self, s: Stat, val: i32) {
+        self.spent.insert(s, val);
+    }
+
+    fn conditions(&self) -> &Vec<Condition> {
+        &self.conditions
+    }
+
+    fn conditions_mut(&mut self) -> &mut Vec<Condition> {
+        &mut self.conditions
+    }
+
+    fn effects(&self) -> &Vec<Effect> {
+        &self.effects
+    }
+
+    fn effects_mut(&mut self) -> &mut Vec<Effect> {
+        &mut self.effects
+    }
+
+    fn get_modifier(&self, s: &String) -> &ModifierValue {
+        match self.modifiers.get(s) {
+            Some(modifier) => modifier.as_ref(),
+            None => panic!("Invalid modifier category in DND backend: {}", s),
+        }
+    }
+
+    fn set_modifier(&mut self, s: String, modifier: Box<ModifierValue>) {
+        self.modifiers.insert(s, modifier);
+    }
+}
+
+fn ability_modifier(score: i32) -> i32 {
+    if score >= 10 {
+        (score - 10) / 2
+    } else {
+        (score - 11) / 2
+    }
+}
+
+pub struct DNDBackend {
+    cal: HarptosCalendar,
+    players: Vec<DNDPlayer>,
+
+    ruleset: Ruleset,
+}
+
+impl DNDBackend {
+    pub fn factory() -> BackendFactory {
+        BackendFactory::new(ID, "Dungeons & Dragons 5e", "1.0", || {
+            DNDBackend::new()
+                .map(|x| Box::new(x) as Box<PenAndPaperBackend>)
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    pub fn new() -> Result<DNDBackend, RulesetError> {
+        Ok(DNDBackend {
+            cal: HarptosCalendar {
+                day: 1,
+                month: 1,
+                year: 1491,
+                hour: 7,
+                minute: 0,
+            },
+            players: Vec::new(),
+            ruleset: Ruleset::load(RULESET)?,
+        })
+    }
+}
+
+impl PenAndPaperBackend for DNDBackend {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
+    fn calendar(&mut self) -> &mut PenAndPaperCalendar {
+        &mut self.cal
+    }
+
+    fn add_player(&mut self, name: String) -> &Player {
+        let mut player = DNDPlayer {
+            _name: name,
+            character_sheet: self.ruleset.defaults(),
+            spent: HashMap::new(),
+            conditions: Vec::new(),
+            effects: Vec::new(),
+            modifiers: HashMap::new(),
+        };
+
+        for modifier in self.character_sheet().modifiers() {
+            if let Some(value) = modifier.get_values(&player).into_iter().next() {
+                player.set_modifier(modifier.name.to_string(), value);
+            }
+        }
+
+        self.players.push(player);
+        self.players.last().unwrap()
+    }
+
+    fn player_count(&self) -> usize {
+        self.players.len()
+    }
+
+    fn get_player(&mut self, pos: usize) -> &mut Player {
+        &mut self.players[pos]
+    }
+
+    fn remove_player(&mut self, pos: usize) {
+        self.players.remove(pos);
+    }
+
+    fn migrations(&self) -> Vec<Vec<Migration>> {
+        Vec::new()
+    }
+
+    fn condition_names(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    fn max_condition_level(&self) -> i32 {
+        0
+    }
+
+    fn experience(&mut self, _: usize) -> Option<Experience> {
+        None
+    }
+
+    fn maximum(&self, _: usize, _: &Stat) -> Option<i32> {
+        None
+    }
+
+    fn validate(&mut self, _: usize) -> Vec<String> {
+        Vec::new()
+    }
+
+    // a long rest restores all hit points
+    fn regenerate(&mut self, pos: usize, _: &mut DiceRoller) -> Vec<Check> {
+        let player = self.get_player(pos);
+        let hp = Stat::Attribute("Hit Points", "HP");
+        let spent = player.get_spent(&hp);
+        if spent <= 0 {
+            return Vec::new();
+        }
+
+        player.set_spent(hp, 0);
+        vec![Check {
+            name: "Long Rest".to_string(),
+            rolls: Vec::new(),
+            confirmation: None,
+            outcome: CheckOutcome::Success,
+            details: format!("+{} HP", spent),
+        }]
+    }
+
+    fn combat_rules(&self) -> CombatRules {
+        CombatRules {
+            initiative: Dice::new(1, 20, 0),
+            round_seconds: 6,
+        }
+    }
+
+    fn initiative(&mut self, pos: usize) -> Result<i32, String> {
+        let stat = Stat::Attribute("Initiative", "INIT");
+        let sheet = self.character_sheet();
+        let category = sheet
+            .categories()
+            .iter()
+            .find(|x| x.find_stat(&stat.identifier().to_string()).is_some())
+            .ok_or_else(|| format!("the ruleset has no stat '{}'", stat.identifier()))?;
+
+        Ok(sheet.calc_value(self.get_player(pos), category, &stat))
+    }
+
+    fn check_options(&self, _: &Stat) -> Option<Vec<CheckOption>> {
+        None
+    }
+
+    fn check(&mut self, _: usize, _: &Stat, _: i32, _: &mut DiceRoller) -> Option<Check> {
+        None
+    }
+
+    fn character_sheet(&mut self) -> CharacterSheet {
+        fn calc(
+            sheet: &CharacterSheet,
+            p: &Player,
+            _: &StatCategory,
+            s: &Stat,
+            value: &Fn(&Stat) -> i32,
+        ) -> Breakdown {
+            let mut val = Breakdown::new(p.get_value(s));
+            for modifier in sheet.modifiers() {
+                let modifier = p.get_modifier(&modifier.name.to_string());
+                val.add(modifier.name(), modifier.get_modifier(s, p.get_value(s)));
+            }
+
+            sheet.add_formula(&mut val, s, value);
+
+            if let Stat::Ability(_, abilities) = s {
+                let proficiency = value(&Stat::Calculated("Proficiency Bonus"));
+                let proficient = min(max(val.total(), 0), 2);
+                let score = value(&Stat::Attribute("", abilities[0]));
+
+                val.set(
+                    format!("{} Modifier", abilities[0]),
+                    ability_modifier(score),
+                );
+                val.add("Proficiency", proficient * proficiency);
+            }
+
+            val.add_effects(p, Some(s));
+            if let Stat::Ability(_, _) = s {
+                val.add_effects(p, None);
+            }
+
+            val
+        }
+
+        let mut sheet = self.ruleset.character_sheet(Box::new(calc));
+
+        let stats: Vec<Stat> = sheet.stats().iter().map(|x| x.1.stat.clone()).collect();
+        for stat in stats {
+            if let Stat::Ability(_, abilities) = &stat {
+                let score = Stat::Attribute("", abilities[0]);
+                sheet.add_dependency(stat.clone(), Stat::Calculated("Proficiency Bonus"));
+                sheet.add_dependency(stat.clone(), score);
+                sheet.add_state_dependency(stat.clone());
+            }
+        }
+
+        sheet
+    }
+}
@@ -2,532 +2,125 @@ use std::cmp::{max, min};
 use std::collections::HashMap;
 
 use crate::application::backend::*;
+use crate::application::dice::*;
+use crate::application::ruleset::*;
 
-pub struct AventurienCalendar {
-    hour: i32,
-    minute: i32,
+const ID: &str = "dsa5";
+const RULESET: &str = "rulesets/dsa.toml";
 
-    day: i32,
-    month: i32,
-    year: i32,
-}
+// The twelve months of the gods followed by the five Nameless Days
+const MONTHS: [(&str, i32); 13] = [
+    ("Praios", 30),
+    ("Rondra", 30),
+    ("Efferd", 30),
+    ("Travia", 30),
+    ("Boron", 30),
+    ("Hesinde", 30),
+    ("Firun", 30),
+    ("Tsa", 30),
+    ("Phex", 30),
+    ("Peraine", 30),
+    ("Ingerimm", 30),
+    ("Rahja", 30),
+    ("Namenlose Tage", 5),
+];
 
-enum CultureMensch {
-    Andergaster,
-    Aranier,
-    Bornlaender,
-    Fjarninger,
-    Horasier,
-    Maraskaner,
-    Mhanadistani,
-    Mittelreicher,
-    Mohas,
-    Nivesen,
-    Norbarden,
-    Nordaventurier,
-    Nostrier,
-    Novadis,
-    Suedaventurier,
-    Svelltaler,
-    Thorwaller,
-    Zyklopaeer,
-}
+const NAMELESS_DAYS: i32 = 13;
 
-impl ModifierValue for CultureMensch {
-    fn name(&self) -> String {
-        match self {
-            CultureMensch::Andergaster => "Andergaster (20 AP)",
-            CultureMensch::Aranier => "Aranier (26 AP)",
-            CultureMensch::Bornlaender => "Bornländer (18 AP)",
-            CultureMensch::Fjarninger => "Fjarninger (33 AP)",
-            CultureMensch::Horasier => "Horasier (27 AP)",
-            CultureMensch::Maraskaner => "Maraskaner (28 AP)",
-            CultureMensch::Mhanadistani => "Mhanadistani (26 AP)",
-            CultureMensch::Mittelreicher => "Mittelreicher (12 AP)",
-            CultureMensch::Mohas => "Moha (38 AP)",
-            CultureMensch::Nivesen => "Nivese (37 AP)",
-            CultureMensch::Norbarden => "Norbarde (18 AP)",
-            CultureMensch::Nordaventurier => "Nordaventurier (25 AP)",
-            CultureMensch::Nostrier => "Nostrier (15 AP)",
-            CultureMensch::Novadis => "Novadis (25 AP)",
-            CultureMensch::Suedaventurier => "Suedaventurier (26 AP)",
-            CultureMensch::Svelltaler => "Svelltaler (21 AP)",
-            CultureMensch::Thorwaller => "Thorwaller (24 AP)",
-            CultureMensch::Zyklopaeer => "Zyklopäer (16 AP)",
-        }
-        .to_string()
-    }
+// 1 Praios 0 BF is counted as a Windstag with a dead Madamal
+const WEEKDAYS: [&str; 7] = [
+    "Windstag",
+    "Erdstag",
+    "Markttag",
+    "Praiostag",
+    "Rohalstag",
+    "Feuertag",
+    "Wassertag",
+];
 
-    fn get_modifier(&self, s: &Stat, _: i32) -> i32 {
-        match self {
-            CultureMensch::Andergaster => match s {
-                Stat::Ability("Holzbearbeitung", _) => 2,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Pflanzenkunde", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 1,
-                Stat::Ability("Tierkunde", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 2,
+const MADAMAL_CYCLE: i32 = 28;
 
-                Stat::Attribute(_, "AP") => -20,
-                _ => 0,
-            },
-            CultureMensch::Aranier => match s {
-                Stat::Ability("Betören", _) => 1,
-                Stat::Ability("Brett- & Glückspiel", _) => 1,
-                Stat::Ability("Gassenwissen", _) => 2,
-                Stat::Ability("Handel", _) => 2,
-                Stat::Ability("Menschenkenntnis", _) => 1,
-                Stat::Ability("Rechnen", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 1,
-                Stat::Ability("Stoffbearbeitung", _) => 1,
-                Stat::Ability("Überreden", _) => 2,
-                Stat::Attribute(_, "AP") => -26,
-                _ => 0,
-            },
-            CultureMensch::Bornlaender => match s {
-                Stat::Ability("Fährtensuche", _) => 1,
-                Stat::Ability("Holzbearbeitung", _) => 2,
-                Stat::Ability("Lebensmittelbearbeitung", _) => 1,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Pflanzenkunde", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 1,
-                Stat::Ability("Zechen", _) => 2,
-                Stat::Attribute(_, "AP") => -18,
-                _ => 0,
-            },
-            CultureMensch::Fjarninger => match s {
-                Stat::Ability("Einschüchtern", _) => 2,
-                Stat::Ability("Fährtensuche", _) => 1,
-                Stat::Ability("Körperbeherrschung", _) => 1,
-                Stat::Ability("Kraftakt", _) => 2,
-                Stat::Ability("Metallbearbeitung", _) => 1,
-                Stat::Ability("Orientierung", _) => 2,
-                Stat::Ability("Selbstbeherrschung", _) => 1,
-                Stat::Ability("Steinbearbeitung", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 2,
-                Stat::Attribute(_, "AP") => -33,
-                _ => 0,
-            },
-            CultureMensch::Horasier => match s {
-                Stat::Ability("Betören", _) => 1,
-                Stat::Ability("Boote & Schiffe", _) => 1,
-                Stat::Ability("Etikette", _) => 2,
-                Stat::Ability("Gassenwissen", _) => 2,
-                Stat::Ability("Geographie", _) => 1,
-                Stat::Ability("Geschichtswissen", _) => 1,
-                Stat::Ability("Handel", _) => 1,
-                Stat::Ability("Mechanik", _) => 1,
-                Stat::Ability("Rechnen", _) => 2,
-                Stat::Ability("Rechtskunde", _) => 2,
-                Stat::Ability("Tanzen", _) => 1,
-                Stat::Attribute(_, "AP") => -27,
-                _ => 0,
-            },
-            CultureMensch::Maraskaner => match s {
-                Stat::Ability("Götter & Kulte", _) => 1,
-                Stat::Ability("Heilkunde Gift", _) => 2,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Pflanzenkunde", _) => 2,
-                Stat::Ability("Tierkunde", _) => 2,
-                Stat::Ability("Verbergen", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 1,
-                Stat::Attribute(_, "AP") => -26,
-                _ => 0,
-            },
-            CultureMensch::Mhanadistani => match s {
-                Stat::Ability("Brett- & Glückspiel", _) => 2,
-                Stat::Ability("Gassenwissen", _) => 2,
-                Stat::Ability("Geschichtswissen", _) => 2,
-                Stat::Ability("Götter & Kulte", _) => 2,
-                Stat::Ability("Handel", _) => 2,
-                Stat::Ability("Magiekunde", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 2,
-                Stat::Ability("Überreden", _) => 1,
-                Stat::Attribute(_, "AP") => -28,
-                _ => 0,
-            },
-            CultureMensch::Mittelreicher => match s {
-                Stat::Ability("Holzbearbeitung", _) => 1,
-                Stat::Ability("Metallbearbeitung", _) => 1,
-                Stat::Ability("Pflanzenkunde", _) => 1,
-                Stat::Ability("Stoffbearbeitung", _) => 1,
-                Stat::Ability("Tierkunde", _) => 1,
-                Stat::Attribute(_, "AP") => -12,
-                _ => 0,
-            },
-            CultureMensch::Mohas => match s {
-                Stat::Ability("Fährtensuche", _) => 1,
-                Stat::Ability("Heilkunde Gift", _) => 1,
-                Stat::Ability("Körperbeherrschung", _) => 1,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Pflanzenkunde", _) => 2,
-                Stat::Ability("Sagen & Legenden", _) => 1,
-                Stat::Ability("Sinnesschärfe", _) => 1,
-                Stat::Ability("Tierkunde", _) => 2,
-                Stat::Ability("Verbergen", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 2,
-                Stat::Attribute(_, "AP") => -38,
-                _ => 0,
-            },
-            CultureMensch::Nivesen => match s {
-                Stat::Ability("Fährtensuche", _) => 2,
-                Stat::Ability("Fahrzeuge", _) => 1,
-                Stat::Ability("Orientierung", _) => 2,
-                Stat::Ability("Pflanzenkunde", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 2,
-                Stat::Ability("Sinnesschärfe", _) => 1,
-                Stat::Ability("Tierkunde", _) => 2,
-                Stat::Ability("Verbergen", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 2,
-                Stat::Attribute(_, "AP") => -37,
-                _ => 0,
-            },
-            CultureMensch::Norbarden => match s {
-                Stat::Ability("Fahrzeuge", _) => 2,
-                Stat::Ability("Geographie", _) => 2,
-                Stat::Ability("Handel", _) => 2,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Überreden", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 1,
-                Stat::Attribute(_, "AP") => -18,
-                _ => 0,
-            },
-            CultureMensch::Nordaventurier => match s {
-                Stat::Ability("Fährtensuche", _) => 2,
-                Stat::Ability("Handel", _) => 1,
-                Stat::Ability("Holzbearbeitung", _) => 1,
-                Stat::Ability("Lederbearbeitung", _) => 1,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Pflanzenkunde", _) => 1,
-                Stat::Ability("Selbstbeherrschung", _) => 1,
-                Stat::Ability("Tierkunde", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 1,
-                Stat::Ability("Zechen", _) => 1,
-                Stat::Attribute(_, "AP") => -25,
-                _ => 0,
-            },
-            CultureMensch::Nostrier => match s {
-                Stat::Ability("Fischen & Angeln", _) => 2,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Pflanzenkunde", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 1,
-                Stat::Ability("Tierkunde", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 1,
-                Stat::Attribute(_, "AP") => -15,
-                _ => 0,
-            },
-            CultureMensch::Novadis => match s {
-                Stat::Ability("Einschüchtern", _) => 2,
-                Stat::Ability("Fährtensuche", _) => 1,
-                Stat::Ability("Orientierung", _) => 2,
-                Stat::Ability("Rechtskunde", _) => 1,
-                Stat::Ability("Reiten", _) => 2,
-                Stat::Ability("Tierkunde", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 2,
-                Stat::Attribute(_, "AP") => -25,
-                _ => 0,
-            },
-            CultureMensch::Suedaventurier => match s {
-                Stat::Ability("Betören", _) => 1,
-                Stat::Ability("Boote & Schiffe", _) => 1,
-                Stat::Ability("Fischen & Angeln", _) => 1,
-                Stat::Ability("Gassenwissen", _) => 2,
-                Stat::Ability("Heilkunde Gift", _) => 1,
-                Stat::Ability("Menschenkenntnis", _) => 2,
-                Stat::Ability("Überreden", _) => 1,
-                Stat::Ability("Willenskraft", _) => 1,
-                Stat::Attribute(_, "AP") => -26,
-                _ => 0,
-            },
-            CultureMensch::Svelltaler => match s {
-                Stat::Ability("Fährtensuche", _) => 2,
-                Stat::Ability("Fahrzeuge", _) => 2,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Pflanzenkunde", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 1,
-                Stat::Ability("Tierkunde", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 1,
-                Stat::Attribute(_, "AP") => -21,
-                _ => 0,
-            },
-            CultureMensch::Thorwaller => match s {
-                Stat::Ability("Boote & Schiffe", _) => 1,
-                Stat::Ability("Einschüchtern", _) => 1,
-                Stat::Ability("Fischen & Angeln", _) => 2,
-                Stat::Ability("Geographie", _) => 1,
-                Stat::Ability("Holzbearbeitung", _) => 2,
-                Stat::Ability("Kraftakt", _) => 2,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 1,
-                Stat::Ability("Zechen", _) => 2,
-                Stat::Attribute(_, "AP") => -24,
-                _ => 0,
-            },
-            CultureMensch::Zyklopaeer => match s {
-                Stat::Ability("Boote & Schiffe", _) => 1,
-                Stat::Ability("Fischen & Angeln", _) => 2,
-                Stat::Ability("Geschichtswissen", _) => 1,
-                Stat::Ability("Götter & Kulte", _) => 2,
-                Stat::Ability("Musizieren", _) => 1,
-                Stat::Ability("Rechnen", _) => 1,
-                Stat::Ability("Rechtskunde", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 1,
-                Stat::Ability("Tanzen", _) => 1,
-                Stat::Attribute(_, "AP") => -16,
-                _ => 0,
-            },
-        }
-    }
-}
+// Holidays as (day, month, name). The year begins at the summer solstice and
+// every god is celebrated on the first day of their month.
+const FESTIVALS: [(i32, i32, &str); 17] = [
+    (1, 1, "Neujahr"),
+    (1, 1, "Sommersonnenwende"),
+    (1, 1, "Praiosfest"),
+    (1, 2, "Rondrafest"),
+    (1, 3, "Efferdfest"),
+    (1, 4, "Tag der Heimkehr"),
+    (1, 4, "Herbst-Tagundnachtgleiche"),
+    (1, 5, "Boronfest"),
+    (1, 6, "Hesindefest"),
+    (1, 7, "Firunfest"),
+    (1, 7, "Wintersonnenwende"),
+    (1, 8, "Tsafest"),
+    (1, 9, "Phexfest"),
+    (1, 10, "Perainefest"),
+    (1, 10, "Frühlings-Tagundnachtgleiche"),
+    (1, 11, "Ingerimmfest"),
+    (1, 12, "Rahjafest"),
+];
 
-enum CultureElf {
-    Auelfen,
-    Firnelfen,
-    Waldelfen,
-}
+const RECKONINGS: [Reckoning; 4] = [
+    Reckoning {
+        name: "Bosparans Fall",
+        abbreviation: "BF",
+        offset: 0,
+    },
+    Reckoning {
+        name: "Kaiser Hal",
+        abbreviation: "Hal",
+        offset: -992,
+    },
+    Reckoning {
+        name: "Horas",
+        abbreviation: "H",
+        offset: 1492,
+    },
+    Reckoning {
+        name: "Zwergische Zeitrechnung",
+        abbreviation: "nT",
+        offset: 4520,
+    },
+];
 
-impl ModifierValue for CultureElf {
-    fn name(&self) -> String {
-        match self {
-            CultureElf::Auelfen => "Auelf (43 AP)",
-            CultureElf::Firnelfen => "Firnelf (55 AP)",
-            CultureElf::Waldelfen => "Waldelf (47 AP)",
-        }
-        .to_string()
-    }
-
-    fn get_modifier(&self, s: &Stat, _: i32) -> i32 {
-        match self {
-            CultureElf::Auelfen => match s {
-                Stat::Ability("Betören", _) => 1,
-                Stat::Ability("Boote & Schiffe", _) => 1,
-                Stat::Ability("Fährtensuche", _) => 1,
-                Stat::Ability("Fischen & Angeln", _) => 2,
-                Stat::Ability("Körperbeherrschung", _) => 2,
-                Stat::Ability("Musizieren", _) => 2,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Pflanzenkunde", _) => 1,
-                Stat::Ability("Schwimmen", _) => 2,
-                Stat::Ability("Singen", _) => 2,
-                Stat::Ability("Sinnesschärfe", _) => 1,
-                Stat::Ability("Tierkunde", _) => 1,
-                Stat::Ability("Verbergen", _) => 1,
-                Stat::Ability("Wildnisleben", _) => 1,
-                Stat::Attribute(_, "AP") => -43,
-                _ => 0,
-            },
-            CultureElf::Firnelfen => match s {
-                Stat::Ability("Fährtensuchen", _) => 2,
-                Stat::Ability("Fischen & Angeln", _) => 1,
-                Stat::Ability("Klettern", _) => 1,
-                Stat::Ability("Körperbeherrschung", _) => 2,
-                Stat::Ability("Musizieren", _) => 2,
-                Stat::Ability("Orientierung", _) => 2,
-                Stat::Ability("Selbstbeherrschung", _) => 1,
-                Stat::Ability("Singen", _) => 2,
-                Stat::Ability("Sinnesschärfe", _) => 2,
-                Stat::Ability("Tierkunde", _) => 2,
-                Stat::Ability("Verbergen", _) => 2,
-                Stat::Ability("Wildnisleben", _) => 2,
-                Stat::Attribute(_, "AP") => -55,
-                _ => 0,
-            },
-            CultureElf::Waldelfen => match s {
-                Stat::Ability("Fährtensuchen", _) => 2,
-                Stat::Ability("Klettern", _) => 1,
-                Stat::Ability("Körperbeherrschung", _) => 2,
-                Stat::Ability("Musizieren", _) => 2,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Pflanzenkunde", _) => 2,
-                Stat::Ability("Singen", _) => 2,
-                Stat::Ability("Sinnesschärfe", _) => 1,
-                Stat::Ability("Tierkunde", _) => 2,
-                Stat::Ability("Verbergen", _) => 2,
-                Stat::Ability("Wildnisleben", _) => 1,
-                Stat::Attribute(_, "AP") => -47,
-                _ => 0,
-            },
-        }
-    }
-}
+pub struct AventurienCalendar {
+    hour: i32,
+    minute: i32,
 
-enum CultureZwerg {
-    Ambosszwerge,
-    Brillantzwerge,
-    Erzzwerge,
-    Huegelzwerge,
-}
+    day: i32,
+    month: i32,
+    year: i32,
 
-impl ModifierValue for CultureZwerg {
-    fn name(&self) -> String {
-        match self {
-            CultureZwerg::Ambosszwerge => "Ambosszwerg (31 AP)",
-            CultureZwerg::Brillantzwerge => "Brillantzwerg (29 AP)",
-            CultureZwerg::Erzzwerge => "Erzzwerg (34 AP)",
-            CultureZwerg::Huegelzwerge => "Hügelzwerg (13 AP)",
-        }
-        .to_string()
-    }
-
-    fn get_modifier(&self, s: &Stat, _: i32) -> i32 {
-        match self {
-            CultureZwerg::Ambosszwerge => match s {
-                Stat::Ability("Einschüchtern", _) => 1,
-                Stat::Ability("Geschichtswissen", _) => 1,
-                Stat::Ability("Kraftakt", _) => 2,
-                Stat::Ability("Kriegskunst", _) => 2,
-                Stat::Ability("Mechanik", _) => 1,
-                Stat::Ability("Metallbearbeitung", _) => 2,
-                Stat::Ability("Orientierung", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 1,
-                Stat::Ability("Steinbearbeitung", _) => 2,
-                Stat::Ability("Verbergen", _) => 1,
-                Stat::Ability("Zechen", _) => 2,
-                Stat::Attribute(_, "AP") => -31,
-                _ => 0,
-            },
-            CultureZwerg::Brillantzwerge => match s {
-                Stat::Ability("Betören", _) => 1,
-                Stat::Ability("Geographie", _) => 2,
-                Stat::Ability("Geschichtswissen", _) => 1,
-                Stat::Ability("Metallbearbeitung", _) => 1,
-                Stat::Ability("Musizieren", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 1,
-                Stat::Ability("Schlösserknacken", _) => 2,
-                Stat::Ability("Steinbearbeitung", _) => 1,
-                Stat::Ability("Tanzen", _) => 1,
-                Stat::Ability("Verbergen", _) => 2,
-                Stat::Ability("Zechen", _) => 1,
-                Stat::Attribute(_, "AP") => -29,
-                _ => 0,
-            },
-            CultureZwerg::Erzzwerge => match s {
-                Stat::Ability("Geschichtswissen", _) => 2,
-                Stat::Ability("Götter & Kulte", _) => 2,
-                Stat::Ability("Mechanik", _) => 2,
-                Stat::Ability("Metallbearbeitung", _) => 1,
-                Stat::Ability("Rechnen", _) => 2,
-                Stat::Ability("Rechtskunde", _) => 1,
-                Stat::Ability("Sagen & Legenden", _) => 2,
-                Stat::Ability("Selbstbeherrschung", _) => 1,
-                Stat::Ability("Steinbearbeitung", _) => 1,
-                Stat::Ability("Verbergen", _) => 2,
-                Stat::Ability("Zechen", _) => 1,
-                Stat::Attribute(_, "AP") => -34,
-                _ => 0,
-            },
-            CultureZwerg::Huegelzwerge => match s {
-                Stat::Ability("Fahrzeuge", _) => 1,
-                Stat::Ability("Fischen & Angeln", _) => 1,
-                Stat::Ability("Lebensmittelbearbeitung", _) => 2,
-                Stat::Ability("Singen", _) => 1,
-                Stat::Ability("Tanzen", _) => 1,
-                Stat::Ability("Verbergen", _) => 2,
-                Stat::Ability("Zechen", _) => 1,
-                Stat::Attribute(_, "AP") => -13,
-                _ => 0,
-            },
-        }
-    }
+    reckoning: usize,
 }
 
-enum Race {
-    Mensch,
-    Elf,
-    Halbelf,
-    Zwerg,
-}
+impl AventurienCalendar {
+    fn month_length(month: i32) -> i32 {
+        MONTHS[(month - 1) as usize].1
+    }
 
-impl ModifierValue for Race {
-    fn name(&self) -> String {
-        match self {
-            Race::Mensch => "Mensch (0 AP)",
-            Race::Elf => "Elf (18 AP)",
-            Race::Halbelf => "Halbelf (0 AP)",
-            Race::Zwerg => "Zwerg (61 AP)",
-        }
-        .to_string()
-    }
-
-    fn get_modifier(&self, s: &Stat, _: i32) -> i32 {
-        match self {
-            Race::Mensch => match s {
-                Stat::Attribute(_, "AP") => 0,
-                Stat::Attribute(_, "LE") => 5,
-                Stat::Attribute(_, "SE") => -5,
-                Stat::Attribute(_, "ZK") => -5,
-                Stat::Attribute(_, "G") => 8,
-                _ => 0,
-            },
-            Race::Elf => match s {
-                Stat::Attribute(_, "AP") => -18,
-                Stat::Attribute(_, "LE") => 2,
-                Stat::Attribute(_, "SE") => -4,
-                Stat::Attribute(_, "ZK") => -6,
-                Stat::Attribute(_, "G") => 8,
-                Stat::Attribute(_, "IN") => 1,
-                Stat::Attribute(_, "GE") => 1,
-                _ => 0,
-            },
-            Race::Halbelf => match s {
-                Stat::Attribute(_, "AP") => 0,
-                Stat::Attribute(_, "LE") => 5,
-                Stat::Attribute(_, "SK") => -4,
-                Stat::Attribute(_, "ZK") => -6,
-                Stat::Attribute(_, "G") => 8,
-                _ => 0,
-            },
-            Race::Zwerg => match s {
-                Stat::Attribute(_, "AP") => -61,
-                Stat::Attribute(_, "LE") => 8,
-                Stat::Attribute(_, "SK") => -4,
-                Stat::Attribute(_, "ZK") => -4,
-                Stat::Attribute(_, "G") => 6,
-                Stat::Attribute(_, "KO") => 1,
-                Stat::Attribute(_, "KK") => 1,
-                _ => 0,
-            },
+    fn next_month(&mut self) {
+        self.month += 1;
+        if self.month > MONTHS.len() as i32 {
+            self.month = 1;
+            self.year += 1;
         }
     }
-}
-
-enum AttributeBonus {
-    MU(i32),
-    KL(i32),
-    IN(i32),
-    CH(i32),
-    FF(i32),
-    GE(i32),
-    KO(i32),
-    KK(i32),
-}
 
-impl ModifierValue for AttributeBonus {
-    fn name(&self) -> String {
-        match self {
-            AttributeBonus::MU(x) => format!("MU {}", x),
-            AttributeBonus::KL(x) => format!("KL {}", x),
-            AttributeBonus::IN(x) => format!("IN {}", x),
-            AttributeBonus::CH(x) => format!("CH {}", x),
-            AttributeBonus::FF(x) => format!("FF {}", x),
-            AttributeBonus::GE(x) => format!("GE {}", x),
-            AttributeBonus::KO(x) => format!("KO {}", x),
-            AttributeBonus::KK(x) => format!("KK {}", x),
+    fn prev_month(&mut self) {
+        self.month -= 1;
+        if self.month < 1 {
+            self.month = MONTHS.len() as i32;
+            self.year -= 1;
         }
     }
 
-    fn get_modifier(&self, s: &Stat, _: i32) -> i32 {
-        match (self, s) {
-            (AttributeBonus::MU(x), Stat::Attribute(_, "MU")) => *x,
-            (AttributeBonus::KL(x), Stat::Attribute(_, "KL")) => *x,
-            (AttributeBonus::IN(x), Stat::Attribute(_, "IN")) => *x,
-            (AttributeBonus::CH(x), Stat::Attribute(_, "CH")) => *x,
-            (AttributeBonus::FF(x), Stat::Attribute(_, "FF")) => *x,
-            (AttributeBonus::GE(x), Stat::Attribute(_, "GE")) => *x,
-            (AttributeBonus::KO(x), Stat::Attribute(_, "KO")) => *x,
-            (AttributeBonus::KK(x), Stat::Attribute(_, "KK")) => *x,
-            _ => 0,
-        }
+    // Days since 1 Praios 0 BF
+    fn days(day: i32, month: i32, year: i32) -> i32 {
+        let months: i32 = (1..month).map(AventurienCalendar::month_length).sum();
+        365 * year + months + day - 1
     }
 }
 
@@ -535,9 +128,10 @@ pub struct DSAPlayer {
     _name: String,
 
     character_sheet: HashMap<Stat, i32>,
-    race: Box<ModifierValue>,
-    culture: Box<ModifierValue>,
-    bonus: Box<ModifierValue>,
+    spent: HashMap<Stat, i32>,
+    conditions: Vec<Condition>,
+    effects: Vec<Effect>,
+    modifiers: HashMap<String, Box<ModifierValue>>,
 }
 
 impl Player for DSAPlayer {
@@ -561,22 +155,39 @@ impl Player for DSAPlayer {
         self.character_sheet.insert(s, val);
     }
 
+    fn get_spent(&self, s: &Stat) -> i32 {
+        self.spent.get(s).cloned().unwrap_or(0)
+    }
+
+    fn set_spent(&mut self, s: Stat, val: i32) {
+        self.spent.insert(s, val);
+    }
+
+    fn conditions(&self) -> &Vec<Condition> {
+        &self.conditions
+    }
+
+    fn conditions_mut(&mut self) -> &mut Vec<Condition> {
+        &mut self.conditions
+    }
+
+    fn effects(&self) -> &Vec<Effect> {
+        &self.effects
+    }
+
+    fn effects_mut(&mut self) -> &mut Vec<Effect> {
+        &mut self.effects
+    }
+
     fn get_modifier(&self, s: &String) -> &ModifierValue {
-        match s.as_str() {
-            "Rasse" => self.race.as_ref(),
-            "Kultur" => self.culture.as_ref(),
-            "Eigenschaftsbonus" => self.bonus.as_ref(),
-            _ => panic!("Invalid modifier category in DSA backend"),
+        match self.modifiers.get(s) {
+            Some(modifier) => modifier.as_ref(),
+            None => panic!("Invalid modifier category in DSA backend: {}", s),
         }
     }
 
     fn set_modifier(&mut self, s: String, modifier: Box<ModifierValue>) {
-        match s.as_str() {
-            "Rasse" => self.race = modifier,
-            "Kultur" => self.culture = modifier,
-            "Eigenschaftsbonus" => self.bonus = modifier,
-            _ => panic!("Invalid modifier category in DSA backend"),
-        }
+        self.modifiers.insert(s, modifier);
     }
 }
 
@@ -595,9 +206,9 @@ impl PenAndPaperCalendar for AventurienCalendar {
     }
 
     fn set_date(&mut self, day: i32, month: i32, year: i32) {
-        self.day = min(max(day, 1), 30);
-        self.month = min(max(month, 1), 12);
         self.year = year;
+        self.month = min(max(month, 1), MONTHS.len() as i32);
+        self.day = min(max(day, 1), AventurienCalendar::month_length(self.month));
     }
 
     fn advance_time(&mut self, t: TimeUnits) {
@@ -623,29 +234,36 @@ impl PenAndPaperCalendar for AventurienCalendar {
                 self.advance_time(TimeUnits::Days(days));
             }
             TimeUnits::Days(d) => {
-                let days = self.day + d;
-                let mut months = (days - 1) / 30;
-                self.day = (days - 1) % 30 + 1;
-
-                if self.day < 1 {
-                    months -= 1;
-                    self.day += 30;
+                let mut day = self.day + d;
+                while day > AventurienCalendar::month_length(self.month) {
+                    day -= AventurienCalendar::month_length(self.month);
+                    self.next_month();
+                }
+                while day < 1 {
+                    self.prev_month();
+                    day += AventurienCalendar::month_length(self.month);
                 }
-                self.advance_time(TimeUnits::Months(months));
+                self.day = day;
             }
             TimeUnits::Weeks(w) => {
                 self.advance_time(TimeUnits::Days(7 * w));
             }
             TimeUnits::Months(m) => {
-                let months = self.month + m;
-                let mut years = (months - 1) / 12;
-                self.month = (months - 1) % 12 + 1;
+                // months skip the Nameless Days and keep the day of the month
+                for _ in 0..m.abs() {
+                    loop {
+                        if m > 0 {
+                            self.next_month()
+                        } else {
+                            self.prev_month()
+                        }
 
-                if self.month < 1 {
-                    years -= 1;
-                    self.month += 12;
+                        if self.month != NAMELESS_DAYS {
+                            break;
+                        }
+                    }
                 }
-                self.advance_time(TimeUnits::Years(years));
+                self.day = min(self.day, AventurienCalendar::month_length(self.month));
             }
             TimeUnits::Years(y) => {
                 self.year += y;
@@ -655,22 +273,51 @@ impl PenAndPaperCalendar for AventurienCalendar {
 
     fn get_month_name(&self, month: i32) -> &'static str {
         match month {
-            1 => "Praios",
-            2 => "Rondra",
-            3 => "Efferd",
-            4 => "Travia",
-            5 => "Boron",
-            6 => "Hesinde",
-            7 => "Firun",
-            8 => "Tsa",
-            9 => "Phex",
-            10 => "Peraine",
-            11 => "Ingerimm",
-            12 => "Rahja",
+            x if x >= 1 && x <= MONTHS.len() as i32 => MONTHS[(x - 1) as usize].0,
             _ => "Undefined",
         }
     }
 
+    fn get_festivals(&self, day: i32, month: i32, _: i32) -> Vec<&'static str> {
+        let mut festivals: Vec<&'static str> = FESTIVALS
+            .iter()
+            .filter(|x| x.0 == day && x.1 == month)
+            .map(|x| x.2)
+            .collect();
+        if month == NAMELESS_DAYS {
+            festivals.push("Namenloser Tag");
+        }
+        festivals
+    }
+
+    fn reckonings(&self) -> Vec<Reckoning> {
+        RECKONINGS.to_vec()
+    }
+
+    fn get_reckoning(&self) -> usize {
+        self.reckoning
+    }
+
+    fn set_reckoning(&mut self, reckoning: usize) {
+        self.reckoning = min(reckoning, RECKONINGS.len() - 1);
+    }
+
+    fn get_weekday_name(&self, day: i32, month: i32, year: i32) -> &'static str {
+        let days = AventurienCalendar::days(day, month, year);
+        WEEKDAYS[days.rem_euclid(WEEKDAYS.len() as i32) as usize]
+    }
+
+    // The Madamal dies, waxes to the chalice, becomes the full wheel and
+    // wanes to the helmet
+    fn get_moon_phase(&self, day: i32, month: i32, year: i32) -> &'static str {
+        match AventurienCalendar::days(day, month, year).rem_euclid(MADAMAL_CYCLE) {
+            0 => "Madamal: Tote Mada (Neumond)",
+            x if x < MADAMAL_CYCLE / 2 => "Madamal: Kelch (zunehmend)",
+            x if x == MADAMAL_CYCLE / 2 => "Madamal: Rad (Vollmond)",
+            _ => "Madamal: Helm (abnehmend)",
+        }
+    }
+
     fn minutes_per_hour(&mut self) -> i32 {
         60
     }
@@ -680,11 +327,11 @@ impl PenAndPaperCalendar for AventurienCalendar {
     fn days_per_week(&mut self) -> i32 {
         7
     }
-    fn days_per_month(&mut self, _: i32) -> i32 {
-        30
+    fn days_per_month(&mut self, month: i32) -> i32 {
+        AventurienCalendar::month_length(month)
     }
     fn months_per_year(&mut self) -> i32 {
-        12
+        MONTHS.len() as i32
     }
 
     fn morning(&self) -> (i32, i32) {
@@ -698,6 +345,7 @@ impl PenAndPaperCalendar for AventurienCalendar {
     }
 }
 
+#[derive(Copy, Clone)]
 enum LevelModificator {
     A,
     B,
@@ -733,75 +381,402 @@ fn get_cost(m: &LevelModificator, mut from: i32, mut to: i32) -> i32 {
     }
 }
 
-fn get_modifier(s: &Stat) -> Option<LevelModificator> {
-    match s {
-        Stat::Attribute(_, _) => Some(LevelModificator::E),
-        Stat::Ability(name, _) => panic!("Unimplemented LevelModifier for {}", name),
-        Stat::Calculated(_) => None,
+fn get_modificator(cost: &str) -> Option<LevelModificator> {
+    match cost {
+        "A" => Some(LevelModificator::A),
+        "B" => Some(LevelModificator::B),
+        "C" => Some(LevelModificator::C),
+        "D" => Some(LevelModificator::D),
+        "E" => Some(LevelModificator::E),
+        _ => None,
+    }
+}
+
+// AP spent on improving stats, by category
+fn category_costs(
+    costs: &HashMap<&'static str, (LevelModificator, i32)>,
+    sheet: &CharacterSheet,
+    p: &Player,
+) -> Vec<(&'static str, i32)> {
+    sheet
+        .categories()
+        .iter()
+        .map(|c| {
+            let cost = c
+                .entries
+                .iter()
+                .filter_map(|entry| match entry {
+                    CategoryEntry::Stat(stat) => costs
+                        .get(stat.stat.identifier())
+                        .map(|(m, base)| get_cost(m, *base, p.get_value(&stat.stat))),
+                    CategoryEntry::Modifier(_) => None,
+                })
+                .sum();
+            (c.name, cost)
+        })
+        .collect()
+}
+
+const CONDITIONS: [&str; 6] = [
+    "Belastung",
+    "Betäubung",
+    "Furcht",
+    "Paralyse",
+    "Schmerz",
+    "Verwirrung",
+];
+
+const MAX_CONDITION_LEVEL: i32 = 4;
+
+const LEVEL: &str = "Erfahrungsgrad";
+
+// The experience levels with the starting AP they grant
+const LEVELS: [(&str, i32); 7] = [
+    ("Unerfahren (900 AP)", 900),
+    ("Durchschnittlich (1000 AP)", 1000),
+    ("Erfahren (1100 AP)", 1100),
+    ("Kompetent (1200 AP)", 1200),
+    ("Meisterlich (1400 AP)", 1400),
+    ("Brillant (1700 AP)", 1700),
+    ("Legendär (2100 AP)", 2100),
+];
+
+// Every level of a condition makes checks one point harder. Levels of the
+// same condition from different sources add up to at most IV.
+fn condition_levels(p: &Player, pain: i32) -> Vec<(&'static str, i32)> {
+    CONDITIONS
+        .iter()
+        .map(|name| {
+            let level = p
+                .conditions()
+                .iter()
+                .filter(|x| x.name == *name)
+                .map(|x| x.level)
+                .sum::<i32>();
+
+            match *name {
+                "Schmerz" => (*name, min(level + pain, MAX_CONDITION_LEVEL)),
+                _ => (*name, min(level, MAX_CONDITION_LEVEL)),
+            }
+        })
+        .collect()
+}
+
+// Schmerz I to IV at 3/4, 1/2 and 1/4 of the maximum LeP and at 5 LeP
+fn pain(current: i32, max: i32) -> i32 {
+    [max * 3 / 4, max / 2, max / 4, 5]
+        .iter()
+        .filter(|x| current <= **x)
+        .count() as i32
+}
+
+pub struct TalentCheck {
+    pub rolls: Vec<i32>,
+    pub points: i32,
+    pub quality: i32,
+    pub outcome: CheckOutcome,
+}
+
+// Rolls 3W20 against the given attributes. A positive modifier eases the
+// check, a negative one makes it harder.
+pub fn talent_check(
+    attributes: &[i32],
+    skill: i32,
+    modifier: i32,
+    rng: &mut DiceRoller,
+) -> TalentCheck {
+    let rolls = Dice::new(attributes.len() as i32, 20, 0).roll(rng).results;
+
+    let points = skill
+        - rolls
+            .iter()
+            .zip(attributes)
+            .map(|(roll, attribute)| max(roll - (attribute + modifier), 0))
+            .sum::<i32>();
+
+    let count = |value| rolls.iter().filter(|x| **x == value).count();
+    let outcome = if count(20) >= 2 {
+        CheckOutcome::Botch
+    } else if count(1) >= 2 {
+        CheckOutcome::CriticalSuccess
+    } else if points >= 0 {
+        CheckOutcome::Success
+    } else {
+        CheckOutcome::Failure
+    };
+
+    TalentCheck {
+        points: points,
+        quality: match outcome.is_success() {
+            true => min(max(points - 1, 0) / 3 + 1, 6),
+            false => 0,
+        },
+        outcome: outcome,
+        rolls: rolls,
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum CombatAction {
+    Attack,
+    Parry,
+    Ranged,
+}
+
+const COMBAT_ACTIONS: [(&str, CombatAction); 3] = [
+    ("attack", CombatAction::Attack),
+    ("parry", CombatAction::Parry),
+    ("ranged", CombatAction::Ranged),
+];
+
+// every further parry in the same combat round is harder
+const PARRIES: [(&str, i32); 4] = [
+    ("1. Parade", 0),
+    ("2. Parade", -3),
+    ("3. Parade", -6),
+    ("4. Parade", -9),
+];
+
+const DISTANCES: [(&str, i32); 3] = [("Nah", 2), ("Mittel", 0), ("Weit", -2)];
+
+const ROUND_SECONDS: i32 = 2;
+
+fn get_action(action: &str) -> Option<CombatAction> {
+    COMBAT_ACTIONS.iter().find(|x| x.0 == action).map(|x| x.1)
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum StatKind {
+    Attribute,
+    Talent,
+    Technique,
+}
+
+const STAT_KINDS: [(&str, StatKind); 3] = [
+    ("attribute", StatKind::Attribute),
+    ("talent", StatKind::Talent),
+    ("technique", StatKind::Technique),
+];
+
+fn get_kind(kind: &str) -> Option<StatKind> {
+    STAT_KINDS.iter().find(|x| x.0 == kind).map(|x| x.1)
+}
+
+pub struct CombatCheck {
+    pub roll: i32,
+    pub confirmation: Option<i32>,
+    pub target: i32,
+    pub outcome: CheckOutcome,
+}
+
+// Rolls 1W20 against an attack, parry or ranged value. A 1 or a 20 has to
+// be confirmed by a second roll to become a critical success or a botch.
+pub fn combat_check(value: i32, modifier: i32, rng: &mut DiceRoller) -> CombatCheck {
+    let target = value + modifier;
+    let roll = rng.roll_die(20);
+
+    let (confirmation, outcome) = match roll {
+        1 => {
+            let confirmation = rng.roll_die(20);
+            match confirmation <= target {
+                true => (Some(confirmation), CheckOutcome::CriticalSuccess),
+                false => (Some(confirmation), CheckOutcome::Success),
+            }
+        }
+        20 => {
+            let confirmation = rng.roll_die(20);
+            match confirmation > target {
+                true => (Some(confirmation), CheckOutcome::Botch),
+                false => (Some(confirmation), CheckOutcome::Failure),
+            }
+        }
+        x if x <= target => (None, CheckOutcome::Success),
+        _ => (None, CheckOutcome::Failure),
+    };
+
+    CombatCheck {
+        roll: roll,
+        confirmation: confirmation,
+        target: target,
+        outcome: outcome,
+    }
+}
+
+// What a night's rest restores of a pool
+#[derive(Copy, Clone)]
+enum Regeneration {
+    Roll(Dice),
+    Fixed(i32),
+}
+
+fn get_regeneration(regeneration: &str) -> Option<Regeneration> {
+    match regeneration.parse::<i32>() {
+        Ok(x) => Some(Regeneration::Fixed(x)),
+        Err(_) => regeneration.parse().ok().map(Regeneration::Roll),
     }
 }
 
 pub struct DSABackend {
     cal: AventurienCalendar,
     players: Vec<DSAPlayer>,
+
+    ruleset: Ruleset,
+    // improvement factor and the value the stat starts at
+    costs: HashMap<&'static str, (LevelModificator, i32)>,
+    actions: HashMap<&'static str, CombatAction>,
+    kinds: HashMap<&'static str, StatKind>,
+    regeneration: HashMap<&'static str, Regeneration>,
 }
 
 impl DSABackend {
-    pub fn new() -> DSABackend {
-        DSABackend {
+    fn combat_action(&self, stat: &Stat) -> Option<CombatAction> {
+        self.actions.get(stat.identifier()).cloned()
+    }
+
+    fn kind(&self, stat: &Stat) -> Option<StatKind> {
+        self.kinds.get(stat.identifier()).cloned()
+    }
+
+    // The limit of the experience level that applies to the stat
+    fn limit(&self, stat: &Stat) -> Option<&'static str> {
+        match self.kind(stat)? {
+            StatKind::Attribute => Some("Eigenschaft"),
+            StatKind::Talent => Some("Fertigkeit"),
+            StatKind::Technique => Some("Kampftechnik"),
+        }
+    }
+
+    pub fn factory() -> BackendFactory {
+        BackendFactory::new(ID, "Das Schwarze Auge 5", "1.0", || {
+            DSABackend::new()
+                .map(|x| Box::new(x) as Box<PenAndPaperBackend>)
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    pub fn new() -> Result<DSABackend, RulesetError> {
+        let ruleset = Ruleset::load(RULESET)?;
+
+        let mut costs = HashMap::new();
+        for stat in ruleset.stats() {
+            if let Some(cost) = &stat.cost {
+                let modificator = get_modificator(cost).ok_or_else(|| {
+                    RulesetError::Invalid(format!(
+                        "unbekannter Steigerungsfaktor '{}' für '{}'",
+                        cost,
+                        stat.stat.identifier()
+                    ))
+                })?;
+                costs.insert(stat.stat.identifier(), (modificator, stat.default));
+            }
+        }
+
+        let mut actions = HashMap::new();
+        for stat in ruleset.stats() {
+            if let Some(action) = &stat.action {
+                let action = get_action(action).ok_or_else(|| {
+                    RulesetError::Invalid(format!(
+                        "unbekannte Aktion '{}' für '{}'",
+                        action,
+                        stat.stat.identifier()
+                    ))
+                })?;
+                actions.insert(stat.stat.identifier(), action);
+            }
+        }
+
+        let mut kinds = HashMap::new();
+        for stat in ruleset.stats() {
+            if let Some(name) = &stat.kind {
+                let kind = get_kind(name).ok_or_else(|| {
+                    RulesetError::Invalid(format!(
+                        "unbekannte Art '{}' für '{}'",
+                        name,
+                        stat.stat.identifier()
+                    ))
+                })?;
+                match (kind, &stat.stat) {
+                    (StatKind::Attribute, Stat::Attribute(_, _)) => (),
+                    (StatKind::Talent, Stat::Ability(_, _)) => (),
+                    (StatKind::Technique, Stat::Ability(_, _)) => (),
+                    _ => {
+                        return Err(RulesetError::Invalid(format!(
+                            "'{}' kann nicht von der Art '{}' sein",
+                            stat.stat.identifier(),
+                            name
+                        )))
+                    }
+                }
+                kinds.insert(stat.stat.identifier(), kind);
+            }
+        }
+
+        let mut regeneration = HashMap::new();
+        for stat in ruleset.stats() {
+            if let Some(amount) = &stat.regeneration {
+                let amount = get_regeneration(amount).ok_or_else(|| {
+                    RulesetError::Invalid(format!(
+                        "unbekannte Regeneration '{}' für '{}'",
+                        amount,
+                        stat.stat.identifier()
+                    ))
+                })?;
+                regeneration.insert(stat.stat.identifier(), amount);
+            }
+        }
+
+        Ok(DSABackend {
             cal: AventurienCalendar {
                 day: 1,
                 month: 1,
                 year: 1000,
+                reckoning: 0,
                 hour: 8,
                 minute: 0,
             },
             players: Vec::new(),
-        }
+            ruleset: ruleset,
+            costs: costs,
+            actions: actions,
+            kinds: kinds,
+            regeneration: regeneration,
+        })
     }
 }
 
 impl PenAndPaperBackend for DSABackend {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
     fn calendar(&mut self) -> &mut PenAndPaperCalendar {
         &mut self.cal
     }
 
     fn add_player(&mut self, name: String) -> &Player {
-        let mut map = HashMap::new();
-        map.insert(Stat::Attribute("Abenteuerpunkte", "AP"), 1000);
-        map.insert(Stat::Attribute("Mut", "MU"), 8);
-        map.insert(Stat::Attribute("Klugheit", "KL"), 8);
-        map.insert(Stat::Attribute("Intuition", "IN"), 8);
-        map.insert(Stat::Attribute("Charisma", "CH"), 8);
-        map.insert(Stat::Attribute("Fingerfertigkeit", "FF"), 8);
-        map.insert(Stat::Attribute("Gewandheit", "GE"), 8);
-        map.insert(Stat::Attribute("Konsitution", "KO"), 8);
-        map.insert(Stat::Attribute("Körperkraft", "KK"), 8);
-        map.insert(Stat::Ability("Armbrüste", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Bögen", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Dolche", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Fechtwaffen", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Hiebwaffen", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Kettenwaffen", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Lanzen", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Raufen", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Schilde", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Schwerter", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Stangenwaffen", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Wurfwaffen", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Zweihandhiebwaffen", vec!["FF"]), 6);
-        map.insert(Stat::Ability("Zweihandschwerter", vec!["FF"]), 6);
-
-        self.players.push(DSAPlayer {
+        let mut player = DSAPlayer {
             _name: name,
-            character_sheet: map,
-            race: Box::new(Race::Mensch),
-            culture: Box::new(CultureMensch::Andergaster),
-            bonus: Box::new(AttributeBonus::MU(1)),
-        });
+            character_sheet: self.ruleset.defaults(),
+            spent: HashMap::new(),
+            conditions: Vec::new(),
+            effects: Vec::new(),
+            modifiers: HashMap::new(),
+        };
+
+        for modifier in self.character_sheet().modifiers() {
+            if let Some(value) = modifier.get_values(&player).into_iter().next() {
+                player.set_modifier(modifier.name.to_string(), value);
+            }
+        }
+
+        self.players.push(player);
         self.players.last().unwrap()
     }
 
+    fn player_count(&self) -> usize {
+        self.players.len()
+    }
+
     fn get_player(&mut self, pos: usize) -> &mut Player {
         &mut self.players[pos]
     }
@@ -810,291 +785,531 @@ impl PenAndPaperBackend for DSABackend {
         self.players.remove(pos);
     }
 
-    fn character_sheet(&mut self) -> CharacterSheet {
-        fn calc(sheet: &CharacterSheet, p: &Player, c: &StatCategory, s: &Stat) -> i32 {
-            let mut val = p.get_value(s);
-            val = val
-                + p.get_modifier(&"Rasse".to_string()).get_modifier(s, val)
-                + p.get_modifier(&"Kultur".to_string()).get_modifier(s, val)
-                + p.get_modifier(&"Eigenschaftsbonus".to_string())
-                    .get_modifier(s, val);
+    fn migrations(&self) -> Vec<Vec<Migration>> {
+        vec![
+            vec![
+                Migration::RenameStat("Schleichen", "Verbergen"),
+                Migration::RenameModifierValue(
+                    "Kultur",
+                    "Suedaventurier (26 AP)",
+                    "Südaventurier (26 AP)",
+                ),
+            ],
+            vec![Migration::RenameStat("(Ent-)Fesseln", "Fesseln")],
+            // the starting AP moved from the AP value to the experience level
+            vec![Migration::MoveStatToModifier("AP", LEVEL, &LEVELS)],
+            vec![Migration::RenameModifierValue(
+                "Kultur",
+                "Thorwaller (24 AP)",
+                "Thorwaler (24 AP)",
+            )],
+        ]
+    }
 
-            match s {
-                Stat::Attribute(_, "LeP") => {
-                    val += calc(sheet, p, c, &Stat::Attribute("Konstitution", "KO")) * 2
-                }
-                Stat::Attribute(_, "SK") => {
-                    val += (calc(sheet, p, c, &Stat::Attribute("Mut", "MU"))
-                        + calc(sheet, p, c, &Stat::Attribute("Klugheit", "KL"))
-                        + calc(sheet, p, c, &Stat::Attribute("Intuition", "IN")))
-                        / 6
-                }
-                Stat::Attribute(_, "ZK") => {
-                    val += (calc(sheet, p, c, &Stat::Attribute("Konstitution", "KO")) * 2
-                        + calc(sheet, p, c, &Stat::Attribute("Körperkraft", "KK")))
-                        / 6
-                }
-                Stat::Attribute(_, "AW") => {
-                    val += calc(sheet, p, c, &Stat::Attribute("Gewandtheit", "GE")) / 2
-                }
-                Stat::Attribute(_, "INI") => {
-                    val += (calc(sheet, p, c, &Stat::Attribute("Mut", "MU"))
-                        + calc(sheet, p, c, &Stat::Attribute("Gewandtheit", "GE")))
-                        / 2
-                }
-                Stat::Attribute(_, "Schips") => val += 3,
-                Stat::Attribute(_, "AP") => {
-                    val -= sheet.categories().iter().fold(0, |val, c| {
-                        val + c.entries.iter().fold(0, |val, entry| {
-                            val + match entry {
-                                CategoryEntry::Stat(stat) => get_modifier(&stat.stat)
-                                    .map(|x| get_cost(&x, 8, p.get_value(&stat.stat)))
-                                    .unwrap_or(0),
-                                _ => 0,
-                            }
-                        })
-                    })
+    fn condition_names(&self) -> Vec<&'static str> {
+        CONDITIONS.to_vec()
+    }
+
+    fn max_condition_level(&self) -> i32 {
+        MAX_CONDITION_LEVEL
+    }
+
+    fn experience(&mut self, pos: usize) -> Option<Experience> {
+        let sheet = self.character_sheet();
+        let player = &self.players[pos];
+        let ap = Stat::Attribute("Abenteuerpunkte", "AP");
+
+        // the experience level grants the starting AP, race, culture and the
+        // like cost AP
+        let mut total = player.get_value(&ap);
+        let mut spent = Vec::new();
+        for modifier in sheet.modifiers() {
+            let value = player.get_modifier(&modifier.name.to_string());
+            match value.get_modifier(&ap, player.get_value(&ap)) {
+                x if x > 0 => total += x,
+                x if x < 0 => spent.push((value.name(), -x)),
+                _ => (),
+            }
+        }
+        spent.extend(
+            category_costs(&self.costs, &sheet, player)
+                .into_iter()
+                .filter(|x| x.1 != 0)
+                .map(|(name, cost)| (name.to_string(), cost)),
+        );
+
+        Some(Experience {
+            total: total,
+            spent: spent,
+        })
+    }
+
+    fn maximum(&self, pos: usize, stat: &Stat) -> Option<i32> {
+        let level = self.players[pos].get_modifier(&LEVEL.to_string());
+        level.get_limit(self.limit(stat)?)
+    }
+
+    fn validate(&mut self, pos: usize) -> Vec<String> {
+        let mut violations = Vec::new();
+        if let Some(experience) = self.experience(pos) {
+            if experience.available() < 0 {
+                violations.push(format!(
+                    "{} AP mehr ausgegeben als verfügbar",
+                    -experience.available()
+                ));
+            }
+        }
+
+        let sheet = self.character_sheet();
+        let player = &self.players[pos];
+        let level = player.get_modifier(&LEVEL.to_string());
+
+        let mut attributes = 0;
+        for (_, description) in sheet.stats() {
+            let stat = &description.stat;
+            let limit = match self.limit(stat) {
+                Some(limit) => limit,
+                None => continue,
+            };
+
+            let value = player.get_value(stat);
+            if limit == "Eigenschaft" {
+                attributes += value;
+            }
+            match level.get_limit(limit) {
+                Some(max) if value > max => violations.push(format!(
+                    "{} {} ist höher als das Maximum {} für {}",
+                    stat.identifier(),
+                    value,
+                    max,
+                    level.name()
+                )),
+                _ => (),
+            }
+        }
+
+        match level.get_limit("Eigenschaftssumme") {
+            Some(max) if attributes > max => violations.push(format!(
+                "Die Summe der Eigenschaften {} ist höher als das Maximum {} für {}",
+                attributes,
+                max,
+                level.name()
+            )),
+            _ => (),
+        }
+
+        // professions and the like require minimum values
+        let stats = sheet.stats();
+        for modifier in sheet.modifiers() {
+            let value = player.get_modifier(&modifier.name.to_string());
+            for (name, min) in value.prerequisites() {
+                let actual = stats.iter().find(|x| x.1.stat.identifier() == name).map(
+                    |(category, description)| sheet.calc_value(player, category, &description.stat),
+                );
+                match actual {
+                    Some(actual) if actual < min => violations.push(format!(
+                        "{} setzt {} {} voraus, {} ist {}",
+                        value.name(),
+                        name,
+                        min,
+                        name,
+                        actual
+                    )),
+                    _ => (),
                 }
-                Stat::Calculated(name) => {
-                    let len = name.len();
-                    if name.ends_with(" - Attacke") {
-                        let ability = c.find_stat(&name.split_at(len - 10).0.to_string()).unwrap();
-                        let level = p.get_value(&ability.stat);
+            }
+        }
 
-                        val += level + (calc(sheet, p, c, &Stat::Attribute("Mut", "MU")) - 8) / 3;
-                    }
-                    if name.ends_with(" - Parade") {
-                        let ability = c.find_stat(&name.split_at(len - 9).0.to_string()).unwrap();
-                        let level = p.get_value(&ability.stat);
-
-                        let max = match &ability.stat {
-                            Stat::Ability(_, attribs) => attribs
-                                .iter()
-                                .map(|x| (calc(sheet, p, c, &Stat::Attribute("", x)) - 8) / 3)
-                                .max()
-                                .unwrap(),
-                            _ => 0,
+        violations
+    }
+
+    fn regenerate(&mut self, pos: usize, rng: &mut DiceRoller) -> Vec<Check> {
+        let sheet = self.character_sheet();
+        let regeneration = self.regeneration.clone();
+        let player = self.get_player(pos);
+
+        let mut checks = Vec::new();
+        for category in sheet.categories() {
+            for entry in &category.entries {
+                match entry {
+                    CategoryEntry::Stat(stat) if stat.pool => {
+                        let regeneration = match regeneration.get(stat.stat.identifier()) {
+                            Some(regeneration) => regeneration,
+                            None => continue,
+                        };
+                        let spent = player.get_spent(&stat.stat);
+                        if spent <= 0 || sheet.calc_value(player, category, &stat.stat) <= 0 {
+                            continue;
+                        }
+
+                        let (amount, rolls) = match regeneration {
+                            Regeneration::Roll(dice) => {
+                                let roll = dice.roll(rng);
+                                (roll.total, roll.results)
+                            }
+                            Regeneration::Fixed(amount) => (*amount, Vec::new()),
                         };
+                        let regenerated = min(max(amount, 0), spent);
+                        player.set_spent(stat.stat.clone(), spent - regenerated);
 
-                        val += level + max;
+                        checks.push(Check {
+                            name: format!("Regeneration {}", stat.stat.identifier()),
+                            rolls: rolls,
+                            confirmation: None,
+                            outcome: CheckOutcome::Success,
+                            details: format!("+{} {}", regenerated, stat.stat.identifier()),
+                        });
                     }
-                    if name.ends_with(" - Fernkampf") {
-                        let ability = c.find_stat(&name.split_at(len - 12).0.to_string()).unwrap();
-                        let level = p.get_value(&ability.stat);
+                    _ => (),
+                }
+            }
+        }
+
+        checks
+    }
+
+    fn combat_rules(&self) -> CombatRules {
+        CombatRules {
+            initiative: Dice::new(1, 6, 0),
+            round_seconds: ROUND_SECONDS,
+        }
+    }
+
+    fn initiative(&mut self, pos: usize) -> Result<i32, String> {
+        let stat = Stat::Attribute("Initiative", "INI");
+        let sheet = self.character_sheet();
+        let category = sheet
+            .categories()
+            .iter()
+            .find(|x| x.find_stat(&stat.identifier().to_string()).is_some())
+            .ok_or_else(|| format!("the ruleset has no stat '{}'", stat.identifier()))?;
+
+        Ok(sheet.calc_value(self.get_player(pos), category, &stat))
+    }
+
+    fn check_options(&self, stat: &Stat) -> Option<Vec<CheckOption>> {
+        match self.kind(stat) {
+            Some(StatKind::Talent) => Some(Vec::new()),
+            _ => match self.combat_action(stat)? {
+                CombatAction::Attack => Some(Vec::new()),
+                CombatAction::Parry => Some(vec![CheckOption {
+                    name: "Parade in dieser Runde",
+                    choices: PARRIES.to_vec(),
+                    per_round: true,
+                }]),
+                CombatAction::Ranged => Some(vec![CheckOption {
+                    name: "Entfernung",
+                    choices: DISTANCES.to_vec(),
+                    per_round: false,
+                }]),
+            },
+        }
+    }
+
+    fn check(
+        &mut self,
+        pos: usize,
+        stat: &Stat,
+        modifier: i32,
+        rng: &mut DiceRoller,
+    ) -> Option<Check> {
+        let sheet = self.character_sheet();
+        let category = sheet
+            .categories()
+            .iter()
+            .find(|x| x.find_stat(&stat.identifier().to_string()).is_some())?;
+        let action = self.combat_action(stat);
+        let kind = self.kind(stat);
+        let player = self.get_player(pos);
+
+        if action.is_some() {
+            let value = sheet.calc_value(player, category, stat);
+            let result = combat_check(value, modifier, rng);
+
+            return Some(Check {
+                name: stat.identifier().to_string(),
+                rolls: vec![result.roll],
+                confirmation: result.confirmation,
+                outcome: result.outcome,
+                details: match result.confirmation {
+                    Some(x) => format!("Zielwert {}, Bestätigungswurf {}", result.target, x),
+                    None => format!("Zielwert {}", result.target),
+                },
+            });
+        }
+
+        let (name, attributes) = match (kind, stat) {
+            (Some(StatKind::Talent), Stat::Ability(name, attributes)) => (name, attributes),
+            _ => return None,
+        };
+
+        let values: Vec<i32> = attributes
+            .iter()
+            .map(|x| sheet.calc_value(player, category, &Stat::Attribute("", x)))
+            .collect();
+        let skill = sheet.calc_value(player, category, stat);
+
+        // conditions and effects on all checks change the attributes of the
+        // check, attack and parry values already include them
+        let modifier = modifier - sheet.calc_value(player, category, &Stat::Calculated("Zustände"))
+            + effect_value(player, None);
+
+        let result = talent_check(&values, skill, modifier, rng);
+
+        Some(Check {
+            name: name.to_string(),
+            details: match result.outcome.is_success() {
+                true => format!("FP {}, QS {}", result.points, result.quality),
+                false => format!("FP {}", result.points),
+            },
+            rolls: result.rolls,
+            confirmation: None,
+            outcome: result.outcome,
+        })
+    }
+
+    fn character_sheet(&mut self) -> CharacterSheet {
+        fn calc(
+            costs: &HashMap<&'static str, (LevelModificator, i32)>,
+            actions: &HashMap<&'static str, CombatAction>,
+            sheet: &CharacterSheet,
+            p: &Player,
+            s: &Stat,
+            value: &Fn(&Stat) -> i32,
+        ) -> Breakdown {
+            let mut val = Breakdown::new(p.get_value(s));
+            for modifier in sheet.modifiers() {
+                let modifier = p.get_modifier(&modifier.name.to_string());
+                val.add(modifier.name(), modifier.get_modifier(s, p.get_value(s)));
+            }
+            val.add_effects(p, Some(s));
 
-                        val += level
-                            + (calc(sheet, p, c, &Stat::Attribute("Fingerfertigkeit", "FF")) - 8)
-                                / 3;
+            sheet.add_formula(&mut val, s, value);
+
+            match s {
+                Stat::Calculated("Schmerz") => {
+                    let lep = Stat::Attribute("Lebensenergie", "LeP");
+                    let max = value(&lep);
+                    let current = max - p.get_spent(&lep);
+                    val.add(format!("LeP {}/{}", current, max), pain(current, max));
+                }
+                Stat::Calculated("Zustände") => {
+                    let pain = value(&Stat::Calculated("Schmerz"));
+                    for (name, level) in condition_levels(p, pain) {
+                        val.add(name, level);
+                    }
+                }
+                Stat::Attribute(_, "AP") => {
+                    for (name, cost) in category_costs(costs, sheet, p) {
+                        val.add(name, -cost);
                     }
                 }
                 _ => (),
             }
 
+            if actions.contains_key(s.identifier()) {
+                val.add("Zustände", -value(&Stat::Calculated("Zustände")));
+                val.add_effects(p, None);
+            }
+
             val
         }
 
-        let mut sheet = CharacterSheet::new(calc);
+        let costs = self.costs.clone();
+        let actions = self.actions.clone();
+        let mut sheet = self.ruleset.character_sheet(Box::new(
+            move |sheet: &CharacterSheet,
+                  p: &Player,
+                  _: &StatCategory,
+                  s: &Stat,
+                  value: &Fn(&Stat) -> i32| calc(&costs, &actions, sheet, p, s, value),
+        ));
 
-        let mut character = StatCategory::new("Charakter");
-        let races = Modifier::new("Rasse", |_| {
-            vec![
-                Box::new(Race::Mensch),
-                Box::new(Race::Halbelf),
-                Box::new(Race::Elf),
-                Box::new(Race::Zwerg),
-            ]
-        });
-        let cultures = Modifier::new("Kultur", |p| {
-            match p.get_modifier(&"Rasse".to_string()).name().as_str() {
-                "Mensch (0 AP)" => vec![
-                    Box::new(CultureMensch::Andergaster),
-                    Box::new(CultureMensch::Aranier),
-                    Box::new(CultureMensch::Bornlaender),
-                    Box::new(CultureMensch::Fjarninger),
-                    Box::new(CultureMensch::Horasier),
-                    Box::new(CultureMensch::Maraskaner),
-                    Box::new(CultureMensch::Mhanadistani),
-                    Box::new(CultureMensch::Mittelreicher),
-                    Box::new(CultureMensch::Mohas),
-                    Box::new(CultureMensch::Nivesen),
-                    Box::new(CultureMensch::Norbarden),
-                    Box::new(CultureMensch::Nordaventurier),
-                    Box::new(CultureMensch::Nostrier),
-                    Box::new(CultureMensch::Novadis),
-                    Box::new(CultureMensch::Suedaventurier),
-                    Box::new(CultureMensch::Svelltaler),
-                    Box::new(CultureMensch::Thorwaller),
-                    Box::new(CultureMensch::Zyklopaeer),
-                ],
-                "Halbelf (0 AP)" => vec![
-                    Box::new(CultureMensch::Andergaster),
-                    Box::new(CultureMensch::Aranier),
-                    Box::new(CultureMensch::Bornlaender),
-                    Box::new(CultureMensch::Fjarninger),
-                    Box::new(CultureMensch::Horasier),
-                    Box::new(CultureMensch::Maraskaner),
-                    Box::new(CultureMensch::Mhanadistani),
-                    Box::new(CultureMensch::Mittelreicher),
-                    Box::new(CultureMensch::Mohas),
-                    Box::new(CultureMensch::Nivesen),
-                    Box::new(CultureMensch::Norbarden),
-                    Box::new(CultureMensch::Nordaventurier),
-                    Box::new(CultureMensch::Nostrier),
-                    Box::new(CultureMensch::Novadis),
-                    Box::new(CultureMensch::Suedaventurier),
-                    Box::new(CultureMensch::Svelltaler),
-                    Box::new(CultureMensch::Thorwaller),
-                    Box::new(CultureMensch::Zyklopaeer),
-                    Box::new(CultureElf::Auelfen),
-                    Box::new(CultureElf::Firnelfen),
-                    Box::new(CultureElf::Waldelfen),
-                ],
-                "Elf (18 AP)" => vec![
-                    Box::new(CultureElf::Auelfen),
-                    Box::new(CultureElf::Firnelfen),
-                    Box::new(CultureElf::Waldelfen),
-                ],
-                "Zwerg (61 AP)" => vec![
-                    Box::new(CultureZwerg::Ambosszwerge),
-                    Box::new(CultureZwerg::Brillantzwerge),
-                    Box::new(CultureZwerg::Erzzwerge),
-                    Box::new(CultureZwerg::Huegelzwerge),
-                ],
-                x => panic!("Unexpected race modifier: {}", x),
+        let pain = Stat::Calculated("Schmerz");
+        let conditions = Stat::Calculated("Zustände");
+        sheet.add_dependency(pain.clone(), Stat::Attribute("Lebensenergie", "LeP"));
+        sheet.add_dependency(conditions.clone(), pain);
+        sheet.add_state_dependency(conditions.clone());
+
+        let stats: Vec<Stat> = sheet.stats().iter().map(|x| x.1.stat.clone()).collect();
+        for stat in stats {
+            if self.combat_action(&stat).is_some() {
+                sheet.add_dependency(stat.clone(), conditions.clone());
+                sheet.add_state_dependency(stat.clone());
             }
-        });
-        let bonus = Modifier::new("Eigenschaftsbonus", |p| {
-            match p.get_modifier(&"Rasse".to_string()).name().as_str() {
-                "Mensch (0 AP)" => vec![
-                    Box::new(AttributeBonus::MU(1)),
-                    Box::new(AttributeBonus::KL(1)),
-                    Box::new(AttributeBonus::IN(1)),
-                    Box::new(AttributeBonus::CH(1)),
-                    Box::new(AttributeBonus::FF(1)),
-                    Box::new(AttributeBonus::GE(1)),
-                    Box::new(AttributeBonus::KO(1)),
-                    Box::new(AttributeBonus::KK(1)),
-                ],
-                "Halbelf (0 AP)" => vec![
-                    Box::new(AttributeBonus::MU(1)),
-                    Box::new(AttributeBonus::KL(1)),
-                    Box::new(AttributeBonus::IN(1)),
-                    Box::new(AttributeBonus::CH(1)),
-                    Box::new(AttributeBonus::FF(1)),
-                    Box::new(AttributeBonus::GE(1)),
-                    Box::new(AttributeBonus::KO(1)),
-                    Box::new(AttributeBonus::KK(1)),
-                ],
-                "Elf (18 AP)" => vec![
-                    Box::new(AttributeBonus::KL(-2)),
-                    Box::new(AttributeBonus::KK(-2)),
-                ],
-                "Zwerg (61 AP)" => vec![
-                    Box::new(AttributeBonus::CH(-2)),
-                    Box::new(AttributeBonus::GE(-2)),
-                ],
-                x => panic!("Unexpected race modifier: {}", x),
+            if self.costs.contains_key(stat.identifier()) {
+                sheet.add_dependency(Stat::Attribute("Abenteuerpunkte", "AP"), stat);
             }
-        });
-
-        character.add_modifier(races);
-        character.add_modifier(cultures);
-        character.add_modifier(bonus);
-        character.add_stat(Stat::Attribute("Abenterpunkte", "AP"), 0, 5000);
-        character.add_stat(Stat::Attribute("Lebensenergie", "LeP"), 0, 127);
-        character.add_stat(Stat::Attribute("Astralenergie", "AsP"), 0, 127);
-        character.add_stat(Stat::Attribute("Karmaenergie", "KaP"), 0, 127);
-        character.add_stat(Stat::Attribute("Seelenenkraft", "SK"), 0, 127);
-        character.add_stat(Stat::Attribute("Zähigkeit", "ZK"), 0, 127);
-        character.add_stat(Stat::Attribute("Ausweichen", "AW"), 0, 127);
-        character.add_stat(Stat::Attribute("Initiative", "INI"), 0, 127);
-        character.add_stat(Stat::Attribute("Geschwindigkeit", "GS"), 0, 127);
-        character.add_stat(Stat::Attribute("Schicksalspunkte", "Schips"), -127, 0);
-        sheet.add_category(character);
-
-        let mut attributes = StatCategory::new("Attribute");
-        attributes.add_stat(Stat::Attribute("Mut", "MU"), 8, 19);
-        attributes.add_stat(Stat::Attribute("Klugheit", "KL"), 8, 19);
-        attributes.add_stat(Stat::Attribute("Intuition", "IN"), 8, 19);
-        attributes.add_stat(Stat::Attribute("Charisma", "CH"), 8, 19);
-        attributes.add_stat(Stat::Attribute("Fingerfertigkeit", "FF"), 8, 19);
-        attributes.add_stat(Stat::Attribute("Gewandheit", "GE"), 8, 19);
-        attributes.add_stat(Stat::Attribute("Konstitution", "KO"), 8, 19);
-        attributes.add_stat(Stat::Attribute("Körperkraft", "KK"), 8, 19);
-        sheet.add_category(attributes);
-
-        let mut weapons = StatCategory::new("Kampftechnik");
-        weapons.add_stat(Stat::Ability("Armbrüste", vec!["FF"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Armbrüste - Fernkampf"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Armbrüste - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Bögen", vec!["FF"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Bögen - Fernkampf"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Bögen - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Dolche", vec!["GE"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Dolche - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Dolche - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Fechtwaffen", vec!["GE"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Fechtwaffen - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Fechtwaffen - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Hiebwaffen", vec!["KK"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Hiebwaffen - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Hiebwaffen - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Kettenwaffen", vec!["KK"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Kettenwaffen - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Kettenwaffen - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Lanzen", vec!["KK"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Lanzen - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Lanzen - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Raufen", vec!["GE", "KK"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Raufen - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Raufen - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Schilde", vec!["KK"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Schilde - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Schilde - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Schwerter", vec!["GE", "KK"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Schwerter - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Schwerter - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Stangenwaffen", vec!["GE", "KK"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Stangenwaffen - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Stangenwaffen - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Wurfwaffen", vec!["FF"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Wurfwaffen - Fernkampf"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Wurfwaffen - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Zweihandhiebwaffen", vec!["KK"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Zweihandhiebwaffen - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Zweihandhiebwaffen - Parade"), 0, 0);
-        weapons.add_stat(Stat::Ability("Zweihandschwerter", vec!["KK"]), -1, 25);
-        weapons.add_stat(Stat::Calculated("Zweihandschwerter - Attacke"), 0, 0);
-        weapons.add_stat(Stat::Calculated("Zweihandschwerter - Parade"), 0, 0);
-        sheet.add_category(weapons);
-
-        let mut physical = StatCategory::new("Körpertalente");
-        physical.add_stat(Stat::Ability("Schleichen", vec!["MU", "IN", "GE"]), -1, 25);
-        physical.add_stat(
-            Stat::Ability("Selbstbeherrschung", vec!["MU", "KO", "KK"]),
-            -1,
-            25,
-        );
-        physical.add_stat(
-            Stat::Ability("Sinnesschärfe", vec!["KL", "IN", "GE"]),
-            -1,
-            25,
-        );
-        physical.add_stat(
-            Stat::Ability("Taschendiebstahl", vec!["KL", "IN", "FF"]),
-            -1,
-            25,
+        }
+
+        sheet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Returns the given rolls in order
+    struct Rolls(Vec<i32>);
+
+    impl DiceRoller for Rolls {
+        fn roll_die(&mut self, _: i32) -> i32 {
+            self.0.remove(0)
+        }
+    }
+
+    fn talent(skill: i32, modifier: i32, rolls: Vec<i32>) -> TalentCheck {
+        talent_check(&[12, 12, 12], skill, modifier, &mut Rolls(rolls))
+    }
+
+    fn combat(rolls: Vec<i32>) -> CombatCheck {
+        combat_check(10, 2, &mut Rolls(rolls))
+    }
+
+    fn calendar(day: i32, month: i32, year: i32) -> AventurienCalendar {
+        AventurienCalendar {
+            day: day,
+            month: month,
+            year: year,
+            reckoning: 0,
+            hour: 8,
+            minute: 0,
+        }
+    }
+
+    #[test]
+    fn talent_check_quality_levels() {
+        let check = talent(7, 0, vec![10, 11, 12]);
+        assert!(check.outcome == CheckOutcome::Success);
+        assert_eq!((check.points, check.quality), (7, 3));
+
+        let check = talent(7, 0, vec![14, 15, 12]);
+        assert!(check.outcome == CheckOutcome::Success);
+        assert_eq!((check.points, check.quality), (2, 1));
+
+        let check = talent(18, 0, vec![1, 2, 3]);
+        assert_eq!((check.points, check.quality), (18, 6));
+
+        let check = talent(7, 0, vec![18, 18, 18]);
+        assert!(check.outcome == CheckOutcome::Failure);
+        assert_eq!((check.points, check.quality), (-11, 0));
+    }
+
+    #[test]
+    fn talent_check_modifier() {
+        let check = talent(7, -2, vec![11, 11, 11]);
+        assert_eq!((check.points, check.quality), (4, 2));
+
+        let check = talent(0, 2, vec![14, 14, 14]);
+        assert!(check.outcome == CheckOutcome::Success);
+        assert_eq!((check.points, check.quality), (0, 1));
+    }
+
+    #[test]
+    fn talent_check_critical_and_botch() {
+        let check = talent(7, 0, vec![1, 1, 18]);
+        assert!(check.outcome == CheckOutcome::CriticalSuccess);
+        assert_eq!(check.quality, 1);
+
+        let check = talent(18, 0, vec![20, 20, 5]);
+        assert!(check.outcome == CheckOutcome::Botch);
+        assert_eq!(check.quality, 0);
+    }
+
+    #[test]
+    fn combat_check_confirmation() {
+        let check = combat(vec![10]);
+        assert!(check.outcome == CheckOutcome::Success);
+        assert_eq!((check.target, check.confirmation), (12, None));
+
+        let check = combat(vec![13]);
+        assert!(check.outcome == CheckOutcome::Failure);
+
+        let check = combat(vec![1, 5]);
+        assert!(check.outcome == CheckOutcome::CriticalSuccess);
+        assert_eq!(check.confirmation, Some(5));
+
+        let check = combat(vec![1, 15]);
+        assert!(check.outcome == CheckOutcome::Success);
+        assert_eq!(check.confirmation, Some(15));
+
+        let check = combat(vec![20, 15]);
+        assert!(check.outcome == CheckOutcome::Botch);
+
+        let check = combat(vec![20, 5]);
+        assert!(check.outcome == CheckOutcome::Failure);
+        assert_eq!(check.confirmation, Some(5));
+    }
+
+    #[test]
+    fn checks_the_talents_of_the_ruleset() {
+        let backend = DSABackend::new().ok().unwrap();
+        let talent = Stat::Ability("Klettern", vec!["MU", "GE", "KK"]);
+        let technique = Stat::Ability("Armbrüste", vec!["FF"]);
+
+        assert!(backend.check_options(&talent).is_some());
+        assert!(backend.check_options(&technique).is_none());
+    }
+
+    #[test]
+    fn limits_follow_the_kind_of_the_stat() {
+        let backend = DSABackend::new().ok().unwrap();
+
+        let limit = |stat| backend.limit(&stat);
+        assert_eq!(limit(Stat::Attribute("Mut", "MU")), Some("Eigenschaft"));
+        assert_eq!(limit(Stat::Attribute("Lebensenergie", "LeP")), None);
+        assert_eq!(
+            limit(Stat::Ability("Klettern", vec!["MU", "GE", "KK"])),
+            Some("Fertigkeit")
         );
-        physical.add_stat(Stat::Ability("Klettern", vec!["GE", "GE", "KK"]), -1, 20);
-        physical.add_stat(Stat::Ability("Athletik", vec!["GE", "GE", "IN"]), -1, 20);
-        physical.add_stat(Stat::Ability("Springen", vec!["GE", "GE", "KK"]), -1, 20);
-        physical.add_stat(
-            Stat::Ability("(Ent-)Fesseln", vec!["GE", "KK", "FF"]),
-            -1,
-            25,
+        assert_eq!(
+            limit(Stat::Ability("Armbrüste", vec!["FF"])),
+            Some("Kampftechnik")
         );
-        sheet.add_category(physical);
+        assert_eq!(limit(Stat::Calculated("Armbrüste - Parade")), None);
+    }
 
-        sheet
+    #[test]
+    fn days_pass_through_the_nameless_days() {
+        let mut cal = calendar(30, 12, 1040);
+        cal.advance_time(TimeUnits::Days(1));
+        assert_eq!(cal.get_date(), (1, NAMELESS_DAYS, 1040));
+        assert_eq!(cal.get_month_name(NAMELESS_DAYS), "Namenlose Tage");
+
+        cal.advance_time(TimeUnits::Days(5));
+        assert_eq!(cal.get_date(), (1, 1, 1041));
+
+        cal.advance_time(TimeUnits::Days(-1));
+        assert_eq!(cal.get_date(), (5, NAMELESS_DAYS, 1040));
+
+        let mut cal = calendar(1, 1, 1040);
+        cal.advance_time(TimeUnits::Days(365));
+        assert_eq!(cal.get_date(), (1, 1, 1041));
+    }
+
+    #[test]
+    fn months_skip_the_nameless_days() {
+        let mut cal = calendar(15, 12, 1040);
+        cal.advance_time(TimeUnits::Months(1));
+        assert_eq!(cal.get_date(), (15, 1, 1041));
+
+        cal.advance_time(TimeUnits::Months(-1));
+        assert_eq!(cal.get_date(), (15, 12, 1040));
+
+        let mut cal = calendar(3, NAMELESS_DAYS, 1040);
+        cal.advance_time(TimeUnits::Months(1));
+        assert_eq!(cal.get_date(), (3, 1, 1041));
+    }
+
+    #[test]
+    fn set_date_clamps_to_the_nameless_days() {
+        let mut cal = calendar(1, 1, 1040);
+        cal.set_date(30, NAMELESS_DAYS, 1040);
+        assert_eq!(cal.get_date(), (5, NAMELESS_DAYS, 1040));
+        assert_eq!(
+            cal.get_festivals(5, NAMELESS_DAYS, 1040),
+            vec!["Namenloser Tag"]
+        );
     }
 }
@@ -0,0 +1,433 @@
+use std::fmt;
+use std::str::FromStr;
+
+pub struct FormulaError(String);
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Ungültige Formel: {}", self.0)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Function {
+    Min,
+    Max,
+    Round,
+    Floor,
+    Ceil,
+}
+
+const FUNCTIONS: [(&str, Function); 5] = [
+    ("min", Function::Min),
+    ("max", Function::Max),
+    ("round", Function::Round),
+    ("floor", Function::Floor),
+    ("ceil", Function::Ceil),
+];
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = FUNCTIONS.iter().find(|x| x.1 == *self).unwrap().0;
+        write!(f, "{}", name)
+    }
+}
+
+// Divides and rounds the quotient with the given rounding function
+fn divide(a: i32, b: i32, round: fn(f64) -> f64) -> i32 {
+    match b {
+        0 => 0,
+        b => round(a as f64 / b as f64) as i32,
+    }
+}
+
+#[derive(Clone)]
+enum Expression {
+    Number(i32),
+    Stat(String),
+    Negate(Box<Expression>),
+    Binary(char, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+impl Expression {
+    fn precedence(&self) -> i32 {
+        match self {
+            Expression::Binary('+', _, _) | Expression::Binary('-', _, _) => 1,
+            Expression::Binary(_, _, _) => 2,
+            _ => 3,
+        }
+    }
+
+    fn evaluate(&self, value: &Fn(&str) -> i32) -> i32 {
+        match self {
+            Expression::Number(x) => *x,
+            Expression::Stat(name) => value(name),
+            Expression::Negate(x) => 0i32.saturating_sub(x.evaluate(value)),
+            Expression::Binary(operator, a, b) => {
+                let (a, b) = (a.evaluate(value), b.evaluate(value));
+                match operator {
+                    '+' => a.saturating_add(b),
+                    '-' => a.saturating_sub(b),
+                    '*' => a.saturating_mul(b),
+                    _ => divide(a, b, f64::trunc),
+                }
+            }
+            Expression::Call(function, arguments) => {
+                let arguments: Vec<i32> = arguments.iter().map(|x| x.evaluate(value)).collect();
+                match function {
+                    Function::Min => *arguments.iter().min().unwrap(),
+                    Function::Max => *arguments.iter().max().unwrap(),
+                    Function::Round => divide(arguments[0], arguments[1], f64::round),
+                    Function::Floor => divide(arguments[0], arguments[1], f64::floor),
+                    Function::Ceil => divide(arguments[0], arguments[1], f64::ceil),
+                }
+            }
+        }
+    }
+
+    fn references(&self, names: &mut Vec<String>) {
+        match self {
+            Expression::Number(_) => (),
+            Expression::Stat(name) => names.push(name.clone()),
+            Expression::Negate(x) => x.references(names),
+            Expression::Binary(_, a, b) => {
+                a.references(names);
+                b.references(names);
+            }
+            Expression::Call(_, arguments) => {
+                for argument in arguments {
+                    argument.references(names);
+                }
+            }
+        }
+    }
+
+    // Splits a sum into its summands, subtracted ones are negated
+    fn terms(self, negate: bool, terms: &mut Vec<Expression>) {
+        match self {
+            Expression::Binary('+', a, b) => {
+                a.terms(negate, terms);
+                b.terms(negate, terms);
+            }
+            Expression::Binary('-', a, b) => {
+                a.terms(negate, terms);
+                b.terms(!negate, terms);
+            }
+            x if negate => terms.push(Expression::Negate(Box::new(x))),
+            x => terms.push(x),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let is_name = |name: &String| name.chars().all(|x| x.is_alphanumeric() || x == '_');
+
+        match self {
+            Expression::Number(x) => write!(f, "{}", x),
+            Expression::Stat(name) if is_name(name) => write!(f, "{}", name),
+            Expression::Stat(name) => write!(f, "[{}]", name),
+            Expression::Negate(x) if x.precedence() < 3 => write!(f, "-({})", x),
+            Expression::Negate(x) => write!(f, "-{}", x),
+            Expression::Binary(operator, a, b) => {
+                let precedence = self.precedence();
+                match a.precedence() < precedence {
+                    true => write!(f, "({})", a)?,
+                    false => write!(f, "{}", a)?,
+                }
+                match operator {
+                    '+' | '-' => write!(f, " {} ", operator)?,
+                    _ => write!(f, "{}", operator)?,
+                }
+                // a - (b + c) and a / (b * c) need parentheses
+                match b.precedence() <= precedence {
+                    true => write!(f, "({})", b),
+                    false => write!(f, "{}", b),
+                }
+            }
+            Expression::Call(function, arguments) => {
+                let arguments: Vec<String> = arguments.iter().map(|x| x.to_string()).collect();
+                write!(f, "{}({})", function, arguments.join(", "))
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> FormulaError {
+        FormulaError(format!(
+            "{} an Position {} in '{}'",
+            message, self.position, self.text
+        ))
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        while self
+            .chars
+            .get(self.position)
+            .map_or(false, |x| x.is_whitespace())
+        {
+            self.position += 1;
+        }
+        self.chars.get(self.position).cloned()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), FormulaError> {
+        match self.peek() {
+            Some(x) if x == c => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => Err(self.error(&format!("'{}' erwartet", c))),
+        }
+    }
+
+    fn take_while(&mut self, f: fn(&char) -> bool) -> String {
+        let start = self.position;
+        while self.chars.get(self.position).map_or(false, f) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn sum(&mut self) -> Result<Expression, FormulaError> {
+        let mut expression = self.product()?;
+        while let Some(operator) = self.peek().filter(|x| *x == '+' || *x == '-') {
+            self.position += 1;
+            expression =
+                Expression::Binary(operator, Box::new(expression), Box::new(self.product()?));
+        }
+        Ok(expression)
+    }
+
+    fn product(&mut self) -> Result<Expression, FormulaError> {
+        let mut expression = self.unary()?;
+        while let Some(operator) = self.peek().filter(|x| *x == '*' || *x == '/') {
+            self.position += 1;
+            expression =
+                Expression::Binary(operator, Box::new(expression), Box::new(self.unary()?));
+        }
+        Ok(expression)
+    }
+
+    fn unary(&mut self) -> Result<Expression, FormulaError> {
+        match self.peek() {
+            Some('-') => {
+                self.position += 1;
+                Ok(Expression::Negate(Box::new(self.unary()?)))
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression, FormulaError> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let expression = self.sum()?;
+                self.expect(')')?;
+                Ok(expression)
+            }
+            Some('[') => {
+                self.position += 1;
+                let name = self.take_while(|x| *x != ']');
+                self.expect(']')?;
+                match name.trim() {
+                    "" => Err(self.error("leerer Name eines Werts")),
+                    name => Ok(Expression::Stat(name.to_string())),
+                }
+            }
+            Some(x) if x.is_ascii_digit() => {
+                let number = self.take_while(char::is_ascii_digit);
+                number
+                    .parse()
+                    .map(Expression::Number)
+                    .map_err(|_| self.error("Zahl zu groß"))
+            }
+            Some(x) if x.is_alphanumeric() || x == '_' => {
+                let name = self.take_while(|x| x.is_alphanumeric() || *x == '_');
+                if self.peek() != Some('(') {
+                    return Ok(Expression::Stat(name));
+                }
+
+                let function = FUNCTIONS
+                    .iter()
+                    .find(|x| x.0 == name)
+                    .map(|x| x.1)
+                    .ok_or_else(|| self.error(&format!("unbekannte Funktion '{}'", name)))?;
+
+                self.position += 1;
+                let mut arguments = vec![self.sum()?];
+                while self.peek() == Some(',') {
+                    self.position += 1;
+                    arguments.push(self.sum()?);
+                }
+                self.expect(')')?;
+
+                match function {
+                    Function::Min | Function::Max => (),
+                    _ if arguments.len() != 2 => {
+                        return Err(self.error(&format!("'{}' braucht zwei Argumente", name)))
+                    }
+                    _ => (),
+                }
+
+                Ok(Expression::Call(function, arguments))
+            }
+            Some(_) => Err(self.error("unerwartetes Zeichen")),
+            None => Err(self.error("unerwartetes Ende")),
+        }
+    }
+}
+
+// An integer expression over stats, e.g. `(MU + KL + IN) / 6`. Stats are
+// referenced by their identifier, names that aren't a single word go in
+// brackets like `[Dolche - Attacke]`. `/` truncates like the integer division
+// of the rules, `round(a, b)`, `floor(a, b)` and `ceil(a, b)` divide `a` by
+// `b` with the respective rounding. `min` and `max` take any number of
+// arguments.
+#[derive(Clone)]
+pub struct Formula {
+    expression: Expression,
+}
+
+impl Formula {
+    pub fn evaluate(&self, value: &Fn(&str) -> i32) -> i32 {
+        self.expression.evaluate(value)
+    }
+
+    // The identifiers of all referenced stats
+    pub fn references(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.expression.references(&mut names);
+        names
+    }
+
+    // The summands of the formula, for itemising the calculated value
+    pub fn terms(&self) -> Vec<Formula> {
+        let mut terms = Vec::new();
+        self.expression.clone().terms(false, &mut terms);
+        terms
+            .into_iter()
+            .map(|x| Formula { expression: x })
+            .collect()
+    }
+}
+
+impl FromStr for Formula {
+    type Err = FormulaError;
+
+    fn from_str(s: &str) -> Result<Formula, FormulaError> {
+        let mut parser = Parser {
+            text: s,
+            chars: s.chars().collect(),
+            position: 0,
+        };
+
+        let expression = parser.sum()?;
+        match parser.peek() {
+            Some(_) => Err(parser.error("unerwartetes Zeichen")),
+            None => Ok(Formula {
+                expression: expression,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(s: &str, value: &Fn(&str) -> i32) -> i32 {
+        s.parse::<Formula>().ok().unwrap().evaluate(value)
+    }
+
+    fn error(s: &str) -> String {
+        match s.parse::<Formula>() {
+            Ok(_) => String::new(),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn evaluates_with_integer_division() {
+        let value = |name: &str| match name {
+            "MU" => 14,
+            "KL" => 12,
+            "IN" => 13,
+            "Dolche - Attacke" => 9,
+            _ => 0,
+        };
+
+        assert_eq!(evaluate("(MU + KL + IN) / 6", &value), 6);
+        assert_eq!(evaluate("MU*2 - (KL - IN) + -3", &value), 26);
+        assert_eq!(evaluate("[Dolche - Attacke] * 2", &value), 18);
+        assert_eq!(evaluate("max((MU - 8) / 3, (KL - 8) / 3, 1)", &value), 2);
+        assert_eq!(
+            evaluate("floor(-3, 2) + ceil(3, 2) + round(5, 2)", &value),
+            3
+        );
+        assert_eq!(evaluate("MU / 0", &value), 0);
+    }
+
+    #[test]
+    fn saturates_instead_of_overflowing() {
+        let value = |name: &str| match name {
+            "MAX" => i32::max_value(),
+            "MIN" => i32::min_value(),
+            _ => 0,
+        };
+
+        assert_eq!(evaluate("MAX + 1", &value), i32::max_value());
+        assert_eq!(evaluate("MIN - 1", &value), i32::min_value());
+        assert_eq!(evaluate("MAX * 2", &value), i32::max_value());
+        assert_eq!(evaluate("-MIN", &value), i32::max_value());
+        assert_eq!(evaluate("MIN / -1", &value), i32::max_value());
+    }
+
+    #[test]
+    fn lists_references_and_terms() {
+        let formula: Formula = "KO*2 - (a - b) + -c".parse().ok().unwrap();
+        assert_eq!(formula.references(), vec!["KO", "a", "b", "c"]);
+
+        let terms: Vec<String> = formula.terms().iter().map(|x| x.to_string()).collect();
+        assert_eq!(terms, vec!["KO*2", "-a", "b", "-c"]);
+    }
+
+    #[test]
+    fn rejects_invalid_formulas() {
+        assert_eq!(
+            error("1 +"),
+            "Ungültige Formel: unerwartetes Ende an Position 3 in '1 +'"
+        );
+        assert_eq!(
+            error("foo(1)"),
+            "Ungültige Formel: unbekannte Funktion 'foo' an Position 3 in 'foo(1)'"
+        );
+        assert_eq!(
+            error("round(1)"),
+            "Ungültige Formel: 'round' braucht zwei Argumente an Position 8 in 'round(1)'"
+        );
+        assert_eq!(
+            error("(1"),
+            "Ungültige Formel: ')' erwartet an Position 2 in '(1'"
+        );
+        assert_eq!(
+            error("3 $"),
+            "Ungültige Formel: unerwartetes Zeichen an Position 2 in '3 $'"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Local};
+
+use crate::application::backend::*;
+
+pub const FILE_FILTER: &str = "Text (*.txt)";
+
+pub struct LogEntry {
+    pub date: (i32, i32, i32),
+    pub time: (i32, i32),
+    pub timestamp: DateTime<Local>,
+    pub player: String,
+    pub check: Check,
+    pub hidden: bool,
+}
+
+impl LogEntry {
+    pub fn new(
+        calendar: &PenAndPaperCalendar,
+        player: &str,
+        check: Check,
+        hidden: bool,
+    ) -> LogEntry {
+        LogEntry {
+            date: calendar.get_date(),
+            time: calendar.get_time(),
+            timestamp: Local::now(),
+            player: player.to_string(),
+            check: check,
+            hidden: hidden,
+        }
+    }
+
+    // The date is shown in the reckoning the calendar uses
+    pub fn format(&self, calendar: &PenAndPaperCalendar) -> String {
+        let (day, month, year) = self.date;
+        let (hour, minute) = self.time;
+        let rolls: Vec<String> = self.check.rolls.iter().map(|x| x.to_string()).collect();
+
+        let text = format!(
+            "{:02}.{:02}.{} {:02}:{:02} ({}) {} - {}: {} [{}] {}",
+            day,
+            month,
+            reckoning(calendar).format(year),
+            hour,
+            minute,
+            self.timestamp.format("%d.%m.%Y %H:%M"),
+            self.player,
+            self.check.name,
+            self.check.outcome,
+            rolls.join(", "),
+            self.check.details
+        );
+
+        match self.hidden {
+            true => format!("{} (verdeckt)", text),
+            false => text,
+        }
+    }
+}
+
+pub struct RollLog {
+    entries: Vec<LogEntry>,
+}
+
+impl RollLog {
+    pub fn new() -> RollLog {
+        RollLog {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, entry: LogEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn entries(&self) -> &Vec<LogEntry> {
+        &self.entries
+    }
+
+    pub fn toggle_hidden(&mut self, index: usize) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.hidden = !entry.hidden;
+        }
+    }
+
+    // Indices of all entries of the given player, or of all entries
+    pub fn filter(&self, player: Option<&str>) -> Vec<usize> {
+        (0..self.entries.len())
+            .filter(|x| player.map_or(true, |p| self.entries[*x].player == p))
+            .collect()
+    }
+
+    pub fn export(&self, calendar: &PenAndPaperCalendar, player: Option<&str>) -> String {
+        self.filter(player)
+            .into_iter()
+            .map(|x| format!("{}\n", self.entries[x].format(calendar)))
+            .collect()
+    }
+}
@@ -8,6 +8,7 @@ use qt_core::variant::Variant;
 use qt_core::abstract_item_model::AbstractItemModel;
 use qt_core::string_list_model::StringListModel;
 
+use qt_widgets::check_box::CheckBox;
 use qt_widgets::combo_box::ComboBox;
 use qt_widgets::label::Label;
 use qt_widgets::layout::Layout;
@@ -21,30 +22,224 @@ use qt_widgets::v_box_layout::VBoxLayout;
 use qt_widgets::button_group::ButtonGroup;
 use qt_widgets::dialog::Dialog;
 
+use std::cmp::{max, min};
+use std::fs;
+
 use crate::qt_bind;
-use qt_bind::{as_object, delete, find_child, find_child_layout, input, iter, load};
+use qt_bind::{
+    as_object, ask, choose, delete, error, find_child, find_child_layout, info, input,
+    input_number, iter, load, open_file_name, save_file_name,
+};
 
+mod agenda;
 pub mod backend;
-mod dsa;
-
+mod campaign;
+mod combat;
+pub mod dice;
+pub mod dnd;
+pub mod dsa;
+mod formula;
+mod log;
+mod ruleset;
+
+use agenda::{Agenda, Event};
 use backend::*;
-use dsa::*;
+use combat::Combat;
+use log::{LogEntry, RollLog};
+
+// How far ahead the main window lists festivals
+const UPCOMING_FESTIVAL_DAYS: i32 = 30;
+
+fn condition_name(condition: &Condition) -> String {
+    const LEVELS: [&str; 6] = ["I", "II", "III", "IV", "V", "VI"];
+    let level = match LEVELS.get((condition.level - 1) as usize) {
+        Some(level) => level.to_string(),
+        None => condition.level.to_string(),
+    };
+
+    match condition.source.is_empty() {
+        true => format!("{} {}", condition.name, level),
+        false => format!("{} {} ({})", condition.name, level, condition.source),
+    }
+}
+
+fn effect_name(effect: &Effect) -> String {
+    let target = match &effect.target {
+        Some(stat) => stat.identifier(),
+        None => "alle Proben",
+    };
+
+    format!("{} ({:+} {})", effect.name, effect.value, target)
+}
+
+// The stats an effect may change
+fn effect_stats(sheet: &CharacterSheet, effect: &Effect) -> Vec<Stat> {
+    match &effect.target {
+        Some(stat) => vec![stat.clone()],
+        None => sheet.state_dependencies().clone(),
+    }
+}
+
+// Disables the days a month doesn't have and moves the selection into it
+fn update_days(dialog: *mut Dialog, days: i32) {
+    for day in 1..31 {
+        let name = format!("day{}", day);
+        let button: *mut PushButton = find_child(dialog, name.as_str()).unwrap();
+        unsafe {
+            (*(find_child::<Widget, _>(dialog, name.as_str()).unwrap())).set_enabled(day <= days);
+            if day > days && (*button).is_checked() {
+                (*(find_child::<PushButton, _>(dialog, format!("day{}", days).as_str()).unwrap()))
+                    .set_checked(true);
+            }
+        }
+    }
+}
+
+// Shows the year in the reckoning selected in the date dialog
+fn update_year(dialog: *mut Dialog, calendar: &PenAndPaperCalendar, year: i32) {
+    let reckonings: *mut ComboBox = find_child(dialog, "reckoning").unwrap();
+    let year_label: *mut Label = find_child(dialog, "year").unwrap();
+    unsafe {
+        let reckoning = calendar.reckonings()[(*reckonings).current_index() as usize];
+        (*year_label).set_text(&qt_string!(reckoning.format(year)));
+    }
+}
+
+fn instant_name(calendar: &PenAndPaperCalendar, instant: Instant) -> String {
+    let (year, month, day, hour, minute) = instant;
+    format!(
+        "{:02}.{:02}.{} {:02}:{:02}",
+        day,
+        month,
+        reckoning(calendar).format(year),
+        hour,
+        minute
+    )
+}
+
+// Parses "15.02.1040 18:30" or "15.02.1040 Abend", the year is given in the
+// reckoning the calendar shows
+fn parse_instant(calendar: &mut PenAndPaperCalendar, text: &str) -> Option<Instant> {
+    let mut parts = text.split_whitespace();
+    let date: Vec<i32> = parts
+        .next()?
+        .split('.')
+        .map(|x| x.parse().ok())
+        .collect::<Option<_>>()?;
+    let (hour, minute) = match parts.next()? {
+        "Morgen" => calendar.morning(),
+        "Mittag" => calendar.noon(),
+        "Abend" => calendar.evening(),
+        time => {
+            let time: Vec<i32> = time
+                .split(':')
+                .map(|x| x.parse().ok())
+                .collect::<Option<_>>()?;
+            match time.as_slice() {
+                [hour, minute] => (*hour, *minute),
+                _ => return None,
+            }
+        }
+    };
+    if date.len() != 3 || parts.next().is_some() {
+        return None;
+    }
+
+    let (day, month) = (date[0], date[1]);
+    let year = reckoning(calendar).to_calendar(date[2]);
+    let valid = month >= 1
+        && month <= calendar.months_per_year()
+        && day >= 1
+        && day <= calendar.days_per_month(month)
+        && hour >= 0
+        && hour < calendar.hours_per_day()
+        && minute >= 0
+        && minute < calendar.minutes_per_hour();
+
+    match valid {
+        true => Some((year, month, day, hour, minute)),
+        false => None,
+    }
+}
+
+fn input_duration(window: *mut Widget, title: &str, label: &str) -> Option<TimeUnits> {
+    let duration = input_number(window, title, label, 1, 1, 999)?;
+    let units = ["Minuten", "Stunden", "Tage", "Wochen", "Monate", "Jahre"]
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>();
+    match choose(window, title, "Einheit:", &units)? {
+        0 => Some(TimeUnits::Minutes(duration)),
+        1 => Some(TimeUnits::Hours(duration)),
+        2 => Some(TimeUnits::Days(duration)),
+        3 => Some(TimeUnits::Weeks(duration)),
+        4 => Some(TimeUnits::Months(duration)),
+        _ => Some(TimeUnits::Years(duration)),
+    }
+}
+
+fn modifier_details(value: &Box<ModifierValue>) -> String {
+    let mut details = Vec::new();
+
+    let abilities = value.abilities();
+    if !abilities.is_empty() {
+        details.push(format!("Sonderfertigkeiten: {}", abilities.join(", ")));
+    }
+
+    let prerequisites: Vec<String> = value
+        .prerequisites()
+        .iter()
+        .map(|(name, min)| format!("{} {}", name, min))
+        .collect();
+    if !prerequisites.is_empty() {
+        details.push(format!("Voraussetzungen: {}", prerequisites.join(", ")));
+    }
+
+    details.join("\n")
+}
 
 pub struct Application {
     player_list_model: CppBox<StringListModel>,
     selected_player_index: Option<usize>,
 
     backend: Option<Box<PenAndPaperBackend>>,
+    registry: BackendRegistry,
+    file: Option<String>,
     main_window: *mut Widget,
+
+    sheet: Option<CharacterSheet>,
+    // one for each player, in the same order
+    caches: Vec<StatCache>,
+
+    log: RollLog,
+    log_model: CppBox<StringListModel>,
+    log_rows: Vec<usize>,
+
+    combat: Option<Combat>,
+    combat_model: CppBox<StringListModel>,
+
+    agenda: Agenda,
+    agenda_model: CppBox<StringListModel>,
 }
 
 impl Application {
-    pub fn new(main_window: *mut Widget) -> Application {
+    pub fn new(main_window: *mut Widget, registry: BackendRegistry) -> Application {
         let app = Application {
             player_list_model: StringListModel::new(()),
             backend: None,
+            registry: registry,
+            file: None,
             main_window: main_window,
             selected_player_index: None,
+            sheet: None,
+            caches: Vec::new(),
+            log: RollLog::new(),
+            log_model: StringListModel::new(()),
+            log_rows: Vec::new(),
+            combat: None,
+            combat_model: StringListModel::new(()),
+            agenda: Agenda::new(),
+            agenda_model: StringListModel::new(()),
         };
 
         let listview: *mut ListView = find_child(main_window, "players").unwrap();
@@ -53,6 +248,24 @@ impl Application {
             (*listview).set_model(model);
         }
 
+        let log_entries: *mut ListView = find_child(main_window, "log_entries").unwrap();
+        let model = app.log_model.as_mut_ptr() as *mut AbstractItemModel;
+        unsafe {
+            (*log_entries).set_model(model);
+        }
+
+        let combatants: *mut ListView = find_child(main_window, "combatants").unwrap();
+        let model = app.combat_model.as_mut_ptr() as *mut AbstractItemModel;
+        unsafe {
+            (*combatants).set_model(model);
+        }
+
+        let events: *mut ListView = find_child(main_window, "events").unwrap();
+        let model = app.agenda_model.as_mut_ptr() as *mut AbstractItemModel;
+        unsafe {
+            (*events).set_model(model);
+        }
+
         app
     }
 
@@ -103,6 +316,21 @@ impl Application {
         }
     }
 
+    fn create_pool_entry(&self, name: &&'static str, short: &&'static str) -> *mut Widget {
+        unsafe {
+            let widget = load("ui/character_sheet/pool.ui");
+            let identifier: *mut Label = find_child(widget, "identifier").unwrap();
+            let calculated: *mut Label = find_child(widget, "calculated").unwrap();
+            let current: *mut Label = find_child(widget, "current").unwrap();
+            (*as_object(widget)).set_object_name(&qt_string!(*short));
+            (*identifier).set_text(&qt_string!(format!("{} ({})", name, short)));
+            (*calculated).set_text(&qt_string!("0"));
+            (*current).set_text(&qt_string!("0"));
+
+            widget
+        }
+    }
+
     fn create_ability_entry(&self, name: &&'static str, stats: &Vec<&'static str>) -> *mut Widget {
         unsafe {
             let widget = load("ui/character_sheet/ability.ui");
@@ -133,204 +361,1119 @@ impl Application {
 
     fn create_stat_entry(&mut self, stat: &StatDescription, category_name: String) -> *mut Widget {
         let widget = match &stat.stat {
+            Stat::Attribute(name, short) if stat.pool => self.create_pool_entry(name, short),
             Stat::Attribute(name, short) => self.create_attribute_entry(name, short),
             Stat::Ability(name, stats) => self.create_ability_entry(name, stats),
             Stat::Calculated(name) => self.create_calculated_entry(name),
         };
 
-        let value: Option<*mut SpinBox> = find_child(widget, "value");
+        let value: Option<*mut SpinBox> = find_child(widget, "value");
+
+        unsafe {
+            if let Some(value) = value {
+                (*value).set_range(stat.min, stat.max);
+                connect!(
+                    value,
+                    SIGNAL!("valueChanged(int)"),
+                    self,
+                    Application,
+                    Application::change_value,
+                    (category_name, stat.stat.clone(), value),
+                    (String, Stat, *mut SpinBox)
+                );
+            }
+        }
+
+        let lose: Option<*mut PushButton> = find_child(widget, "lose");
+        let restore: Option<*mut PushButton> = find_child(widget, "restore");
+
+        if let (Some(lose), Some(restore)) = (lose, restore) {
+            connect!(
+                lose,
+                SIGNAL!("pressed()"),
+                self,
+                Application,
+                Application::change_pool,
+                (stat.stat.clone(), 1),
+                (Stat, i32)
+            );
+            connect!(
+                restore,
+                SIGNAL!("pressed()"),
+                self,
+                Application,
+                Application::change_pool,
+                (stat.stat.clone(), -1),
+                (Stat, i32)
+            );
+        }
+
+        let roll: Option<*mut PushButton> = find_child(widget, "roll");
+
+        let checkable = match &self.backend {
+            Some(backend) => backend.check_options(&stat.stat).is_some(),
+            None => false,
+        };
+
+        if let Some(roll) = roll {
+            unsafe {
+                (*roll).set_visible(checkable);
+            }
+
+            connect!(
+                roll,
+                SIGNAL!("pressed()"),
+                self,
+                Application,
+                Application::roll_check,
+                stat.stat.clone(),
+                Stat
+            );
+        }
+
+        widget
+    }
+
+    fn build_character_sheet(&mut self) {
+        if let Some(backend) = &mut self.backend {
+            let layout: *mut VBoxLayout = find_child(self.main_window, "character_sheet").unwrap();
+
+            unsafe {
+                let mut child = (*layout).take_at(0);
+                while !child.is_null() {
+                    delete(child as *mut Object);
+                    child = (*layout).take_at(0);
+                }
+            }
+
+            let character_sheet = backend.character_sheet();
+            self.caches = (0..backend.player_count())
+                .map(|i| StatCache::new(&character_sheet, backend.get_player(i)))
+                .collect();
+
+            for category in character_sheet.categories() {
+                unsafe {
+                    (*layout).add_widget(self.create_category_header(category));
+                }
+
+                for entry in &category.entries {
+                    let widget = match entry {
+                        CategoryEntry::Modifier(modifier) => self.create_modifier_entry(modifier),
+                        CategoryEntry::Stat(stat) => {
+                            self.create_stat_entry(stat, category.name.to_string())
+                        }
+                    };
+
+                    unsafe {
+                        (*layout).add_widget(widget);
+                    }
+                }
+
+                unsafe {
+                    (*layout).add_spacing(25);
+                }
+            }
+
+            self.sheet = Some(character_sheet);
+        }
+    }
+
+    // Shows the whole sheet of the selected player
+    fn update_character_sheet(&mut self) {
+        self.update_modifiers();
+
+        let stats = match &self.sheet {
+            Some(sheet) => sheet.stats().iter().map(|x| x.1.stat.clone()).collect(),
+            None => Vec::new(),
+        };
+        self.update_ranges();
+        self.update_stats(&stats);
+
+        self.update_conditions();
+        self.update_effects();
+        self.update_experience();
+    }
+
+    // Returns whether the current value of a modifier wasn't available
+    // anymore and had to be replaced
+    fn update_modifiers(&mut self) -> bool {
+        let mut replaced = false;
+
+        if let (Some(backend), Some(sheet), Some(player_index)) =
+            (&mut self.backend, &self.sheet, self.selected_player_index)
+        {
+            let layout: *mut VBoxLayout = find_child(self.main_window, "character_sheet").unwrap();
+            let player = backend.get_player(player_index);
+            let cache = &mut self.caches[player_index];
+
+            for modifier in sheet.modifiers() {
+                let values = modifier.get_values(player);
+                let current = player.get_modifier(&modifier.name.to_string());
+                let index = values
+                    .iter()
+                    .position(|x| x.name() == current.name())
+                    .unwrap_or_else(|| {
+                        let value = modifier.get_values(player).swap_remove(0);
+                        cache.set_modifier(sheet, player, modifier.name.to_string(), value);
+                        replaced = true;
+                        0
+                    });
+
+                let entry: *mut Layout = find_child_layout(layout, modifier.name).unwrap();
+                let combobox: *mut ComboBox = find_child(entry, "values").unwrap();
+                unsafe {
+                    (*as_object(combobox)).block_signals(true);
+                    while (*combobox).count() > 0 {
+                        (*combobox).remove_item(0);
+                    }
+                    for value in &values {
+                        (*combobox).add_item(&qt_string!(value.name()));
+                    }
+                    (*combobox).set_current_index(index as i32);
+                    (*combobox).set_tool_tip(&qt_string!(modifier_details(&values[index])));
+                    (*as_object(combobox)).block_signals(false);
+                }
+            }
+        }
+
+        replaced
+    }
+
+    // Experience levels and the like limit the values a player may buy.
+    // Values above the limit stay selectable, `validate` reports them.
+    fn update_ranges(&mut self) {
+        if let (Some(backend), Some(sheet), Some(player_index)) =
+            (&mut self.backend, &self.sheet, self.selected_player_index)
+        {
+            let layout: *mut VBoxLayout = find_child(self.main_window, "character_sheet").unwrap();
+
+            for (_, stat) in sheet.stats() {
+                let widget: *mut Widget = match &stat.stat {
+                    Stat::Attribute(_, short) => find_child_layout(layout, short),
+                    Stat::Ability(name, _) => find_child_layout(layout, name),
+                    Stat::Calculated(name) => find_child_layout(layout, name),
+                }
+                .unwrap();
+                let spinbox: Option<*mut SpinBox> = find_child(widget, "value");
+
+                if let Some(spinbox) = spinbox {
+                    let maximum = match backend.maximum(player_index, &stat.stat) {
+                        Some(limit) => {
+                            let value = backend.get_player(player_index).get_value(&stat.stat);
+                            min(stat.max, max(limit, value))
+                        }
+                        None => stat.max,
+                    };
+
+                    // the spinbox may still show the value of another player
+                    unsafe {
+                        (*as_object(spinbox)).block_signals(true);
+                        (*spinbox).set_range(stat.min, maximum);
+                        (*as_object(spinbox)).block_signals(false);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn validate(&mut self) {
+        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
+        {
+            let violations = backend.validate(player_index);
+            let text = match violations.is_empty() {
+                true => "Keine Regelverstöße gefunden.".to_string(),
+                false => violations.join("\n"),
+            };
+
+            info(self.main_window, "Regelprüfung", &text);
+        }
+    }
+
+    // Updates the widgets of the given stats from the cache
+    fn update_stats(&mut self, stats: &Vec<Stat>) {
+        if let (Some(backend), Some(sheet), Some(player_index)) =
+            (&mut self.backend, &self.sheet, self.selected_player_index)
+        {
+            let layout: *mut VBoxLayout = find_child(self.main_window, "character_sheet").unwrap();
+            let player = backend.get_player(player_index);
+            let cache = &self.caches[player_index];
+
+            for (_, stat) in sheet.stats() {
+                if !stats.contains(&stat.stat) {
+                    continue;
+                }
+
+                let widget: *mut Widget = match &stat.stat {
+                    Stat::Attribute(_, short) => find_child_layout(layout, short),
+                    Stat::Ability(name, _) => find_child_layout(layout, name),
+                    Stat::Calculated(name) => find_child_layout(layout, name),
+                }
+                .unwrap();
+                let breakdown = cache.get(&stat.stat).unwrap();
+                let calculated = breakdown.total();
+                let val = player.get_value(&stat.stat);
+                let calculated_label: *mut Label = find_child(widget, "calculated").unwrap();
+                unsafe {
+                    (*calculated_label).set_text(&qt_string!(calculated));
+                    (*calculated_label).set_tool_tip(&qt_string!(breakdown.to_string()));
+
+                    if stat.pool {
+                        let current: *mut Label = find_child(widget, "current").unwrap();
+                        let spent = player.get_spent(&stat.stat);
+                        (*current)
+                            .set_text(&qt_string!(format!("Aktuell: {}", calculated - spent)));
+                    }
+
+                    let spinbox: Option<*mut SpinBox> = find_child(widget, "value");
+                    if let Some(spinbox) = spinbox {
+                        (*as_object(spinbox)).block_signals(true);
+                        (*spinbox).set_value(val);
+                        (*as_object(spinbox)).block_signals(false);
+                    };
+                }
+            }
+        }
+    }
+
+    fn update_conditions(&mut self) {
+        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
+        {
+            let player = backend.get_player(player_index);
+            let conditions: Vec<String> = player.conditions().iter().map(condition_name).collect();
+
+            let label: *mut Label = find_child(self.main_window, "conditions").unwrap();
+            unsafe {
+                (*label).set_text(&qt_string!(match conditions.is_empty() {
+                    true => "Zustände: keine".to_string(),
+                    false => format!("Zustände: {}", conditions.join(", ")),
+                }));
+            }
+        }
+    }
+
+    fn update_experience(&mut self) {
+        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
+        {
+            let label: *mut Label = find_child(self.main_window, "experience").unwrap();
+            let experience = backend.experience(player_index);
+
+            unsafe {
+                (*label).set_visible(experience.is_some());
+
+                if let Some(experience) = experience {
+                    let text = format!(
+                        "AP: {} gesamt, {} ausgegeben, {} verfügbar",
+                        experience.total,
+                        experience.spent_total(),
+                        experience.available()
+                    );
+                    (*label).set_text(&qt_string!(match experience.available() < 0 {
+                        true => format!("{} (Budget überschritten)", text),
+                        false => text,
+                    }));
+
+                    let spent: Vec<String> = experience
+                        .spent
+                        .iter()
+                        .map(|(name, cost)| format!("{}: {}", name, cost))
+                        .collect();
+                    (*label).set_tool_tip(&qt_string!(spent.join("\n")));
+                }
+            }
+        }
+    }
+
+    fn update_effects(&mut self) {
+        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
+        {
+            let effects: Vec<(String, Instant)> = backend
+                .get_player(player_index)
+                .effects()
+                .iter()
+                .map(|x| (effect_name(x), x.expires))
+                .collect();
+            let calendar = backend.calendar();
+            let effects: Vec<String> = effects
+                .into_iter()
+                .map(|(name, expires)| format!("{} bis {}", name, instant_name(calendar, expires)))
+                .collect();
+
+            let label: *mut Label = find_child(self.main_window, "effects").unwrap();
+            unsafe {
+                (*label).set_text(&qt_string!(match effects.is_empty() {
+                    true => "Effekte: keine".to_string(),
+                    false => format!("Effekte: {}", effects.join(", ")),
+                }));
+            }
+        }
+    }
+
+    pub fn add_effect(&mut self) {
+        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
+        {
+            let title = "Effekt hinzufügen";
+            let name = match input(self.main_window, title, "Name (z.B. Heiltrank):", "") {
+                Some(name) => name,
+                None => return,
+            };
+
+            let sheet = backend.character_sheet();
+            let mut targets = vec![None];
+            for category in sheet.categories() {
+                for entry in &category.entries {
+                    if let CategoryEntry::Stat(stat) = entry {
+                        targets.push(Some(stat.stat.clone()));
+                    }
+                }
+            }
+            let names: Vec<String> = targets
+                .iter()
+                .map(|x| match x {
+                    Some(stat) => stat.identifier().to_string(),
+                    None => "Alle Proben".to_string(),
+                })
+                .collect();
+            let target = match choose(self.main_window, title, "Wirkt auf:", &names) {
+                Some(index) => targets.swap_remove(index),
+                None => return,
+            };
+
+            let value = match input_number(self.main_window, title, "Wert:", 1, -99, 99) {
+                Some(value) => value,
+                None => return,
+            };
+            let duration = match input_duration(self.main_window, title, "Dauer:") {
+                Some(duration) => duration,
+                None => return,
+            };
+
+            let effect = Effect {
+                name: name,
+                target: target,
+                value: value,
+                expires: after(backend.calendar(), duration),
+            };
+            if let Some(sheet) = &self.sheet {
+                let player = backend.get_player(player_index);
+                player.effects_mut().push(effect.clone());
+                self.caches[player_index].recalculate(sheet, player, effect_stats(sheet, &effect));
+            }
+
+            self.update_character_sheet();
+        }
+    }
+
+    pub fn remove_effect(&mut self) {
+        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
+        {
+            let player = backend.get_player(player_index);
+            let effects: Vec<String> = player.effects().iter().map(effect_name).collect();
+            if effects.is_empty() {
+                return;
+            }
+
+            if let Some(index) = choose(
+                self.main_window,
+                "Effekt entfernen",
+                "Effekt auswählen:",
+                &effects,
+            ) {
+                let effect = player.effects_mut().remove(index);
+                if let Some(sheet) = &self.sheet {
+                    self.caches[player_index].recalculate(
+                        sheet,
+                        player,
+                        effect_stats(sheet, &effect),
+                    );
+                }
+                self.update_character_sheet();
+            }
+        }
+    }
+
+    fn choose_condition(&mut self, title: &str) -> Option<usize> {
+        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
+        {
+            let player = backend.get_player(player_index);
+            let conditions: Vec<String> = player.conditions().iter().map(condition_name).collect();
+
+            if !conditions.is_empty() {
+                return choose(self.main_window, title, "Zustand auswählen:", &conditions);
+            }
+        }
+
+        None
+    }
+
+    pub fn add_condition(&mut self) {
+        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
+        {
+            let title = "Zustand hinzufügen";
+            let names: Vec<String> = backend
+                .condition_names()
+                .iter()
+                .map(|x| x.to_string())
+                .collect();
+            if names.is_empty() {
+                error(
+                    self.main_window,
+                    title,
+                    "Das Regelwerk kennt keine Zustände.",
+                );
+                return;
+            }
+
+            let name = match choose(self.main_window, title, "Zustand auswählen:", &names) {
+                Some(index) => backend.condition_names()[index],
+                None => return,
+            };
+            let max_level = backend.max_condition_level();
+            let level = match input_number(self.main_window, title, "Stufe:", 1, 1, max_level) {
+                Some(level) => level,
+                None => return,
+            };
+            let source = match input(self.main_window, title, "Ursache:", "") {
+                Some(source) => source,
+                None => return,
+            };
+
+            let conditions = backend.get_player(player_index).conditions_mut();
+            match conditions
+                .iter_mut()
+                .find(|x| x.name == name && x.source == source)
+            {
+                Some(condition) => condition.level = min(condition.level + level, max_level),
+                None => conditions.push(Condition {
+                    name: name,
+                    level: level,
+                    source: source,
+                }),
+            }
+        }
+
+        self.conditions_changed();
+    }
+
+    // Recalculates the stats reading the conditions of the selected player
+    fn conditions_changed(&mut self) {
+        if let (Some(backend), Some(sheet), Some(player_index)) =
+            (&mut self.backend, &self.sheet, self.selected_player_index)
+        {
+            let player = backend.get_player(player_index);
+            let stats = sheet.state_dependencies().clone();
+            self.caches[player_index].recalculate(sheet, player, stats);
+        }
+
+        self.update_character_sheet();
+    }
+
+    pub fn reduce_condition(&mut self) {
+        if let Some(index) = self.choose_condition("Zustand verringern") {
+            if let (Some(backend), Some(player_index)) =
+                (&mut self.backend, self.selected_player_index)
+            {
+                let conditions = backend.get_player(player_index).conditions_mut();
+                conditions[index].level -= 1;
+                if conditions[index].level <= 0 {
+                    conditions.remove(index);
+                }
+            }
+
+            self.conditions_changed();
+        }
+    }
+
+    pub fn remove_condition(&mut self) {
+        if let Some(index) = self.choose_condition("Zustand entfernen") {
+            if let (Some(backend), Some(player_index)) =
+                (&mut self.backend, self.selected_player_index)
+            {
+                backend
+                    .get_player(player_index)
+                    .conditions_mut()
+                    .remove(index);
+            }
+
+            self.conditions_changed();
+        }
+    }
+
+    pub fn change_modifier(&mut self, (name, widget): &mut (String, *mut ComboBox)) {
+        let changed = match (&mut self.backend, &self.sheet, self.selected_player_index) {
+            (Some(backend), Some(sheet), Some(player_index)) => {
+                let player = backend.get_player(player_index);
+
+                let index = unsafe { (**widget).current_index() as usize };
+                let value = match sheet
+                    .modifiers()
+                    .into_iter()
+                    .find(|x| x.name == name.as_str())
+                {
+                    Some(modifier) => modifier.get_values(player).swap_remove(index),
+                    None => panic!("Application::change_modifier: Unknown modifier {}", name),
+                };
+                self.caches[player_index].set_modifier(sheet, player, name.to_string(), value)
+            }
+            _ => return,
+        };
+
+        // other modifiers may depend on this one
+        match self.update_modifiers() {
+            true => self.update_character_sheet(),
+            false => {
+                self.update_ranges();
+                self.update_stats(&changed);
+                self.update_experience();
+            }
+        }
+    }
+
+    pub fn change_value(&mut self, arg: &mut (String, Stat, *mut SpinBox)) {
+        let changed = match (&mut self.backend, &self.sheet, self.selected_player_index) {
+            (Some(backend), Some(sheet), Some(player_index)) => {
+                let player = backend.get_player(player_index);
+                let value = unsafe { (*arg.2).value() };
+
+                self.caches[player_index].set_value(sheet, player, arg.1.clone(), value)
+            }
+            _ => return,
+        };
+
+        self.update_stats(&changed);
+        self.update_experience();
+    }
+
+    pub fn change_pool(&mut self, (stat, direction): &mut (Stat, i32)) {
+        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
+        {
+            let label = match direction {
+                x if *x > 0 => "Verlorene Punkte:",
+                _ => "Zurückerhaltene Punkte:",
+            };
+            let amount = match input_number(self.main_window, stat.identifier(), label, 1, 1, 999) {
+                Some(amount) => amount,
+                None => return,
+            };
+
+            let changed = match &self.sheet {
+                Some(sheet) => {
+                    let player = backend.get_player(player_index);
+                    let spent = player.get_spent(stat);
+                    self.caches[player_index].set_spent(
+                        sheet,
+                        player,
+                        stat.clone(),
+                        max(spent + *direction * amount, 0),
+                    )
+                }
+                _ => return,
+            };
+            self.update_stats(&changed);
+        }
+    }
+
+    pub fn roll_check(&mut self, stat: &mut Stat) {
+        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
+        {
+            let title = format!("Probe auf {}", stat.identifier());
+            let options = backend.check_options(stat).unwrap_or_default();
+
+            let mut combatant = self
+                .combat
+                .as_mut()
+                .and_then(|x| x.find_player(player_index));
+
+            let mut modifier = 0;
+            let mut actions = Vec::new();
+            for option in options {
+                if let (true, Some(combatant)) = (option.per_round, &combatant) {
+                    let index = min(combatant.actions(option.name), option.choices.len() - 1);
+                    modifier += option.choices[index].1;
+                    actions.push(option.name);
+                    continue;
+                }
+
+                let names: Vec<String> = option
+                    .choices
+                    .iter()
+                    .map(|(name, value)| format!("{} ({:+})", name, value))
+                    .collect();
+
+                match choose(self.main_window, &title, option.name, &names) {
+                    Some(index) => modifier += option.choices[index].1,
+                    None => return,
+                }
+            }
+
+            modifier += match input_number(
+                self.main_window,
+                &title,
+                "Erleichterung (+) / Erschwernis (-):",
+                0,
+                -20,
+                20,
+            ) {
+                Some(modifier) => modifier,
+                None => return,
+            };
+
+            match backend.check(player_index, stat, modifier, &mut rand::thread_rng()) {
+                Some(check) => {
+                    let hidden_rolls: *mut CheckBox =
+                        find_child(self.main_window, "hidden_rolls").unwrap();
+                    let hidden = unsafe { (*hidden_rolls).is_checked() };
+
+                    let rolls: Vec<String> = check.rolls.iter().map(|x| x.to_string()).collect();
+                    info(
+                        self.main_window,
+                        &title,
+                        &format!(
+                            "{}: {}\n\nWürfe: {}\n{}",
+                            check.name,
+                            check.outcome,
+                            rolls.join(", "),
+                            check.details
+                        ),
+                    );
+
+                    if let Some(combatant) = &mut combatant {
+                        for action in actions {
+                            combatant.use_action(action);
+                        }
+                    }
+
+                    let player = backend.get_player(player_index).name().clone();
+                    self.log
+                        .add(LogEntry::new(backend.calendar(), &player, check, hidden));
+                    self.update_log();
+                }
+                None => error(
+                    self.main_window,
+                    &title,
+                    "Das Regelwerk unterstützt keine Probe auf diesen Wert.",
+                ),
+            }
+        }
+    }
+
+    fn update_player_list(&mut self) {
+        let model = &mut self.player_list_model;
+        let row_count = model.row_count(());
+        model.remove_rows((0, row_count));
+
+        if let Some(backend) = &mut self.backend {
+            for i in 0..backend.player_count() {
+                model.insert_row(i as i32);
+                let index = model.index(i as i32);
+                model.set_data((
+                    &index,
+                    &Variant::new0(&qt_string!(backend.get_player(i).name())),
+                ));
+            }
+        }
+    }
+
+    fn log_filter(&self) -> Option<String> {
+        let filter: *mut ComboBox = find_child(self.main_window, "log_filter").unwrap();
+        unsafe {
+            match (*filter).current_index() {
+                x if x > 0 => Some((*filter).current_text().to_std_string()),
+                _ => None,
+            }
+        }
+    }
+
+    fn update_log(&mut self) {
+        let selected = self.log_filter();
+
+        let mut players: Vec<&String> = self.log.entries().iter().map(|x| &x.player).collect();
+        players.sort();
+        players.dedup();
+
+        let filter: *mut ComboBox = find_child(self.main_window, "log_filter").unwrap();
+        unsafe {
+            (*as_object(filter)).block_signals(true);
+            while (*filter).count() > 0 {
+                (*filter).remove_item(0);
+            }
+            (*filter).add_item(&qt_string!("Alle Spieler"));
+            for player in &players {
+                (*filter).add_item(&qt_string!(*player));
+            }
+            let index = players
+                .iter()
+                .position(|x| Some(*x) == selected.as_ref())
+                .map_or(0, |x| x + 1);
+            (*filter).set_current_index(index as i32);
+            (*as_object(filter)).block_signals(false);
+        }
+
+        self.log_rows = self
+            .log
+            .filter(self.log_filter().as_ref().map(|x| x.as_str()));
+
+        let model = &mut self.log_model;
+        let row_count = model.row_count(());
+        model.remove_rows((0, row_count));
+
+        if let Some(backend) = &mut self.backend {
+            let calendar = backend.calendar();
+            for (row, entry) in self.log_rows.iter().enumerate() {
+                model.insert_row(row as i32);
+                let index = model.index(row as i32);
+                let text = self.log.entries()[*entry].format(calendar);
+                model.set_data((&index, &Variant::new0(&qt_string!(text))));
+            }
+        }
+    }
+
+    pub fn filter_log(&mut self) {
+        self.update_log();
+    }
+
+    pub fn toggle_hidden(&mut self) {
+        let listview: *mut ListView = find_child(self.main_window, "log_entries").unwrap();
+        let indexes = unsafe { (*(*listview).selection_model()).selected_indexes() };
+
+        for val in iter(&indexes) {
+            if let Some(entry) = self.log_rows.get(val.row() as usize) {
+                self.log.toggle_hidden(*entry);
+            }
+        }
+
+        self.update_log();
+    }
+
+    pub fn export_log(&mut self) {
+        let filter = self.log_filter();
+        let backend = match &mut self.backend {
+            Some(backend) => backend,
+            None => return,
+        };
 
-        unsafe {
-            if let Some(value) = value {
-                (*value).set_range(stat.min, stat.max);
-                connect!(
-                    value,
-                    SIGNAL!("valueChanged(int)"),
-                    self,
-                    Application,
-                    Application::change_value,
-                    (category_name, stat.stat.clone(), value),
-                    (String, Stat, *mut SpinBox)
+        if let Some(path) = save_file_name(
+            self.main_window,
+            "Würfelprotokoll exportieren",
+            log::FILE_FILTER,
+        ) {
+            let text = self
+                .log
+                .export(backend.calendar(), filter.as_ref().map(|x| x.as_str()));
+            if let Err(e) = fs::write(&path, text) {
+                error(
+                    self.main_window,
+                    "Würfelprotokoll exportieren",
+                    &format!("{}\n\n{}", path, e),
                 );
             }
         }
+    }
 
-        widget
+    fn update_agenda(&mut self) {
+        let model = &mut self.agenda_model;
+        let row_count = model.row_count(());
+        model.remove_rows((0, row_count));
+
+        if let Some(backend) = &mut self.backend {
+            let calendar = backend.calendar();
+            for (row, event) in self.agenda.events().iter().enumerate() {
+                model.insert_row(row as i32);
+                let index = model.index(row as i32);
+                model.set_data((
+                    &index,
+                    &Variant::new0(&qt_string!(format!(
+                        "{} - {}",
+                        instant_name(calendar, event.at),
+                        event.name
+                    ))),
+                ));
+            }
+        }
     }
 
-    fn build_character_sheet(&mut self) {
+    pub fn add_event(&mut self) {
         if let Some(backend) = &mut self.backend {
-            let layout: *mut VBoxLayout = find_child(self.main_window, "character_sheet").unwrap();
+            let title = "Termin hinzufügen";
+            let name = match input(
+                self.main_window,
+                title,
+                "Name (z.B. Karawane trifft ein):",
+                "",
+            ) {
+                Some(name) => name,
+                None => return,
+            };
 
-            unsafe {
-                let mut child = (*layout).take_at(0);
-                while !child.is_null() {
-                    delete(child as *mut Object);
-                    child = (*layout).take_at(0);
+            let calendar = backend.calendar();
+            let options = ["Nach einer Dauer", "Zu Datum und Uhrzeit"]
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>();
+            let at = match choose(self.main_window, title, "Zeitpunkt:", &options) {
+                Some(0) => match input_duration(self.main_window, title, "In:") {
+                    Some(duration) => after(calendar, duration),
+                    None => return,
+                },
+                Some(_) => {
+                    let (day, month, year) = calendar.get_date();
+                    let text = match input(
+                        self.main_window,
+                        title,
+                        "Datum und Uhrzeit (z.B. 15.02.1040 18:00 oder 15.02.1040 Abend):",
+                        &format!(
+                            "{:02}.{:02}.{} Abend",
+                            day,
+                            month,
+                            reckoning(calendar).from_calendar(year)
+                        ),
+                    ) {
+                        Some(text) => text,
+                        None => return,
+                    };
+                    match parse_instant(calendar, &text) {
+                        Some(at) => at,
+                        None => {
+                            error(self.main_window, title, "Ungültiges Datum");
+                            return;
+                        }
+                    }
                 }
+                None => return,
+            };
+
+            if at <= now(calendar) {
+                error(
+                    self.main_window,
+                    title,
+                    "Der Termin liegt in der Vergangenheit",
+                );
+                return;
             }
 
-            let character_sheet = backend.character_sheet();
+            self.agenda.add(Event { name: name, at: at });
+            self.update_agenda();
+        }
+    }
 
-            for category in character_sheet.categories() {
-                unsafe {
-                    (*layout).add_widget(self.create_category_header(category));
-                }
+    pub fn remove_event(&mut self) {
+        if let Some(backend) = &mut self.backend {
+            let calendar = backend.calendar();
+            let events: Vec<String> = self
+                .agenda
+                .events()
+                .iter()
+                .map(|x| format!("{} - {}", instant_name(calendar, x.at), x.name))
+                .collect();
+            if events.is_empty() {
+                return;
+            }
 
-                for entry in &category.entries {
-                    let widget = match entry {
-                        CategoryEntry::Modifier(modifier) => self.create_modifier_entry(modifier),
-                        CategoryEntry::Stat(stat) => {
-                            self.create_stat_entry(stat, category.name.to_string())
-                        }
+            if let Some(index) = choose(self.main_window, "Termin entfernen", "Termin:", &events) {
+                self.agenda.remove(index);
+                self.update_agenda();
+            }
+        }
+    }
+
+    fn update_combat(&mut self) {
+        let round: *mut Label = find_child(self.main_window, "round").unwrap();
+        let model = &mut self.combat_model;
+        let row_count = model.row_count(());
+        model.remove_rows((0, row_count));
+
+        match &self.combat {
+            Some(combat) => {
+                for (row, combatant) in combat.combatants().iter().enumerate() {
+                    let marker = match row == combat.current() {
+                        true => "▶",
+                        false => "",
                     };
 
-                    unsafe {
-                        (*layout).add_widget(widget);
-                    }
+                    model.insert_row(row as i32);
+                    let index = model.index(row as i32);
+                    model.set_data((
+                        &index,
+                        &Variant::new0(&qt_string!(format!(
+                            "{} {} (INI {})",
+                            marker, combatant.name, combatant.initiative
+                        ))),
+                    ));
                 }
 
                 unsafe {
-                    (*layout).add_spacing(25);
+                    (*round).set_text(&qt_string!(format!("Kampfrunde {}", combat.round())));
                 }
             }
+            None => unsafe {
+                (*round).set_text(&qt_string!("Kein Kampf"));
+            },
         }
     }
 
-    fn update_character_sheet(&mut self) {
-        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
-        {
-            let layout: *mut VBoxLayout = find_child(self.main_window, "character_sheet").unwrap();
-            let sheet = backend.character_sheet();
-
-            for category in sheet.categories() {
-                let player = backend.get_player(player_index);
-
-                for entry in &category.entries {
-                    match entry {
-                        CategoryEntry::Modifier(modifier) => {
-                            let values = modifier.get_values(player);
-                            let current = player.get_modifier(&modifier.name.to_string());
-                            let index = values
-                                .iter()
-                                .position(|x| x.name() == current.name())
-                                .unwrap_or_else(|| {
-                                    player.set_modifier(
-                                        modifier.name.to_string(),
-                                        modifier.get_values(player).swap_remove(0),
-                                    );
-                                    0
-                                });
-
-                            let entry: *mut Layout =
-                                find_child_layout(layout, modifier.name).unwrap();
-                            let combobox: *mut ComboBox = find_child(entry, "values").unwrap();
-                            unsafe {
-                                (*as_object(combobox)).block_signals(true);
-                                while (*combobox).count() > 0 {
-                                    (*combobox).remove_item(0);
-                                }
-                                for value in values {
-                                    (*combobox).add_item(&qt_string!(value.name()));
-                                }
-                                (*combobox).set_current_index(index as i32);
-                                (*as_object(combobox)).block_signals(false);
-                            }
-                        }
-                        CategoryEntry::Stat(stat) => {
-                            let widget: *mut Widget = match &stat.stat {
-                                Stat::Attribute(_, short) => find_child_layout(layout, short),
-                                Stat::Ability(name, _) => find_child_layout(layout, name),
-                                Stat::Calculated(name) => find_child_layout(layout, name),
-                            }
-                            .unwrap();
-                            let calculated = sheet.calc_value(player, &category, &stat.stat);
-                            let val = player.get_value(&stat.stat);
-                            let calculated_label: *mut Label =
-                                find_child(widget, "calculated").unwrap();
-                            unsafe {
-                                (*calculated_label).set_text(&qt_string!(calculated));
-
-                                let spinbox: Option<*mut SpinBox> = find_child(widget, "value");
-                                if let Some(spinbox) = spinbox {
-                                    (*spinbox).set_value(val);
-                                };
-                            }
-                        }
+    pub fn start_combat(&mut self) {
+        if let Some(backend) = &mut self.backend {
+            let rules = backend.combat_rules();
+            let mut combat = Combat::new(rules.initiative, rules.round_seconds);
+
+            for i in 0..backend.player_count() {
+                let base = match backend.initiative(i) {
+                    Ok(base) => base,
+                    Err(e) => {
+                        error(
+                            self.main_window,
+                            "Kampf beginnen",
+                            &format!("Initiative kann nicht berechnet werden: {}", e),
+                        );
+                        return;
                     }
-                }
+                };
+                let name = backend.get_player(i).name().clone();
+                combat.add(name, Some(i), base, &mut rand::thread_rng());
             }
+
+            self.combat = Some(combat);
+            self.update_combat();
         }
     }
 
-    pub fn change_modifier(&mut self, (name, widget): &mut (String, *mut ComboBox)) {
-        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
-        {
-            let mut sheet = backend.character_sheet();
-
-            let player = backend.get_player(player_index);
+    pub fn add_npc(&mut self) {
+        if self.combat.is_none() {
+            self.start_combat();
+        }
 
-            let index = unsafe { (**widget).current_index() as usize };
-            let value = match sheet
-                .categories_mut()
-                .iter_mut()
-                .map(|x| {
-                    x.entries.iter_mut().find(|y| {
-                        if let CategoryEntry::Modifier(m) = y {
-                            &m.name == name
-                        } else {
-                            false
-                        }
-                    })
-                })
-                .find(|x| x.is_some())
-                .unwrap()
-                .unwrap()
-            {
-                CategoryEntry::Modifier(modifier) => modifier.get_values(player).swap_remove(index),
-                _ => panic!("Application::change_value: Expected modifier as type"),
+        if let Some(combat) = &mut self.combat {
+            let name = match input(self.main_window, "NSC hinzufügen", "Name eingeben:", "") {
+                Some(name) => name,
+                None => return,
             };
-            player.set_modifier(name.to_string(), value);
-            self.update_character_sheet();
+            let base = match input_number(
+                self.main_window,
+                "NSC hinzufügen",
+                "Initiative-Basiswert:",
+                10,
+                0,
+                50,
+            ) {
+                Some(base) => base,
+                None => return,
+            };
+
+            combat.add(name, None, base, &mut rand::thread_rng());
+            self.update_combat();
         }
     }
 
-    pub fn change_value(&mut self, arg: &mut (String, Stat, *mut SpinBox)) {
-        if let (Some(backend), Some(player_index)) = (&mut self.backend, self.selected_player_index)
-        {
-            let player = backend.get_player(player_index);
-            let value;
-            unsafe {
-                value = (*arg.2).value();
-                (*as_object(arg.2)).block_signals(true);
-            }
-
-            player.set_value(arg.1.clone(), value);
-            self.update_character_sheet();
-
-            unsafe {
-                (*as_object(arg.2)).block_signals(false);
+    pub fn next_turn(&mut self) {
+        if let Some(combat) = &mut self.combat {
+            if let Some(minutes) = combat.next_turn() {
+                self.add_time(&TimeUnits::Minutes(minutes));
             }
+            self.update_combat();
         }
     }
 
-    pub fn new_file(&mut self) {
-        println!("New stub");
-        self.backend = Some(Box::new(DSABackend::new()));
+    pub fn end_combat(&mut self) {
+        self.combat = None;
+        self.update_combat();
+    }
+
+    fn show_campaign(&mut self) {
         unsafe {
             (*(find_child::<Widget, _>(self.main_window, "centralwidget").unwrap()))
                 .set_enabled(true);
         }
 
+        self.selected_player_index = None;
         self.build_character_sheet();
         self.update_time();
         self.update_date();
-        let row_count = self.player_list_model.row_count(());
-        self.player_list_model.remove_rows((0, row_count));
+        self.update_player_list();
+        self.update_log();
+        self.update_agenda();
+        self.end_combat();
+    }
+
+    pub fn new_file(&mut self) {
+        let names: Vec<String> = self
+            .registry
+            .factories()
+            .iter()
+            .map(|x| format!("{} ({})", x.name, x.version))
+            .collect();
+
+        let factory = match choose(
+            self.main_window,
+            "Neue Kampagne",
+            "Regelwerk auswählen:",
+            &names,
+        ) {
+            Some(index) => &self.registry.factories()[index],
+            None => return,
+        };
+
+        match factory.create() {
+            Ok(backend) => {
+                self.backend = Some(backend);
+                self.log = RollLog::new();
+                self.agenda = Agenda::new();
+                self.file = None;
+                self.show_campaign();
+            }
+            Err(e) => error(self.main_window, "Neue Kampagne", &e),
+        }
     }
 
     pub fn open(&mut self) {
-        println!("Open stub");
+        if let Some(path) =
+            open_file_name(self.main_window, "Kampagne öffnen", campaign::FILE_FILTER)
+        {
+            match campaign::load(&self.registry, &path) {
+                Ok((backend, log, agenda)) => {
+                    self.backend = Some(backend);
+                    self.log = log;
+                    self.agenda = agenda;
+                    self.file = Some(path);
+                    self.show_campaign();
+                }
+                Err(e) => error(
+                    self.main_window,
+                    "Kampagne öffnen",
+                    &format!("{}\n\n{}", path, e),
+                ),
+            }
+        }
+    }
+
+    fn save_to(&mut self, path: String) {
+        if let Some(backend) = &mut self.backend {
+            match campaign::save(backend.as_mut(), &self.log, &self.agenda, &path) {
+                Ok(()) => self.file = Some(path),
+                Err(e) => error(
+                    self.main_window,
+                    "Kampagne speichern",
+                    &format!("{}\n\n{}", path, e),
+                ),
+            }
+        }
+    }
+
+    pub fn save(&mut self) {
+        match self.file.clone() {
+            Some(path) => self.save_to(path),
+            None => self.save_as(),
+        }
+    }
+
+    pub fn save_as(&mut self) {
+        if self.backend.is_some() {
+            if let Some(path) = save_file_name(
+                self.main_window,
+                "Kampagne speichern",
+                campaign::FILE_FILTER,
+            ) {
+                self.save_to(path);
+            }
+        }
     }
 
     pub fn close(&self) {
@@ -367,6 +1510,9 @@ impl Application {
             ) {
                 Some(str) => {
                     let player = var.add_player(str);
+                    if let Some(sheet) = &self.sheet {
+                        self.caches.push(StatCache::new(sheet, player));
+                    }
 
                     let model = &mut self.player_list_model;
                     let row_count = model.row_count(());
@@ -410,10 +1556,16 @@ impl Application {
 
             for val in iter(&indexes) {
                 var.remove_player(val.row() as usize);
+                self.caches.remove(val.row() as usize);
+                if let Some(combat) = &mut self.combat {
+                    combat.remove_player(val.row() as usize);
+                }
 
                 self.player_list_model.remove_rows((val.row(), 1));
             }
         }
+
+        self.update_combat();
     }
 
     pub fn update_time(&mut self) {
@@ -430,20 +1582,157 @@ impl Application {
     pub fn update_date(&mut self) {
         if let Some(backend) = &mut self.backend {
             let date_label: *mut Label = find_child(self.main_window, "date").unwrap();
+            let day_label: *mut Label = find_child(self.main_window, "day").unwrap();
+
+            let calendar = backend.calendar();
+            let (day, month, year) = calendar.get_date();
+            let details: Vec<&str> = vec![
+                calendar.get_weekday_name(day, month, year),
+                calendar.get_moon_phase(day, month, year),
+            ]
+            .into_iter()
+            .filter(|x| !x.is_empty())
+            .collect();
 
-            let (day, month, year) = backend.calendar().get_date();
             unsafe {
-                (*date_label).set_text(&qt_string!(format!("{:02}.{:02}.{:04}", day, month, year)));
+                (*date_label).set_text(&qt_string!(format!(
+                    "{:02}.{:02}.{}",
+                    day,
+                    month,
+                    reckoning(calendar).format(year)
+                )));
+                (*day_label).set_text(&qt_string!(details.join(", ")));
             }
         }
+
+        self.update_festivals();
     }
 
-    pub fn add_time(&mut self, delta: &TimeUnits) {
+    fn update_festivals(&mut self) {
+        if let Some(backend) = &mut self.backend {
+            let label: *mut Label = find_child(self.main_window, "festivals").unwrap();
+
+            let calendar = backend.calendar();
+            let (day, month, year) = calendar.get_date();
+            let today = calendar.get_festivals(day, month, year);
+            let (year_end, month_end, day_end, _, _) =
+                after(calendar, TimeUnits::Days(UPCOMING_FESTIVAL_DAYS));
+            let upcoming = festivals(calendar, (day, month, year), (day_end, month_end, year_end));
+
+            let text = match (today.is_empty(), upcoming.first()) {
+                (false, _) => format!("Heute: {}", today.join(", ")),
+                (true, Some(((day, month, _), name))) => {
+                    format!("Bald: {} ({:02}.{:02}.)", name, day, month)
+                }
+                (true, None) => String::new(),
+            };
+            let tooltip: Vec<String> = upcoming
+                .iter()
+                .map(|((day, month, _), name)| format!("{:02}.{:02}.: {}", day, month, name))
+                .collect();
+
+            unsafe {
+                (*label).set_text(&qt_string!(text));
+                (*label).set_tool_tip(&qt_string!(tooltip.join("\n")));
+            }
+        }
+    }
+
+    // Lists the festivals between `from` and the current date
+    fn show_passed_festivals(&mut self, from: (i32, i32, i32)) {
+        if let Some(backend) = &mut self.backend {
+            let calendar = backend.calendar();
+            let to = calendar.get_date();
+            let passed: Vec<String> = festivals(calendar, from, to)
+                .iter()
+                .map(|((day, month, _), name)| format!("{:02}.{:02}.: {}", day, month, name))
+                .collect();
+
+            if !passed.is_empty() {
+                info(self.main_window, "Feiertage", &passed.join("\n"));
+            }
+        }
+    }
+
+    fn time_changed(&mut self) {
+        self.update_time();
+        self.update_date();
+        self.expire_effects();
+    }
+
+    fn expire_effects(&mut self) {
+        if let Some(backend) = &mut self.backend {
+            let now = now(backend.calendar());
+
+            let mut expired = Vec::new();
+            for i in 0..backend.player_count() {
+                let player = backend.get_player(i);
+                let name = player.name().clone();
+
+                let mut stats = Vec::new();
+                for effect in player.effects().iter().filter(|x| x.expires <= now) {
+                    expired.push(format!("{}: {}", name, effect_name(effect)));
+                    if let Some(sheet) = &self.sheet {
+                        stats.extend(effect_stats(sheet, effect));
+                    }
+                }
+                player.effects_mut().retain(|x| x.expires > now);
+
+                if let Some(sheet) = &self.sheet {
+                    self.caches[i].recalculate(sheet, player, stats);
+                }
+            }
+
+            if !expired.is_empty() {
+                info(self.main_window, "Abgelaufene Effekte", &expired.join("\n"));
+                self.update_character_sheet();
+            }
+        }
+    }
+
+    // Advances time, stopping at a scheduled event if the GM wants to.
+    // Returns whether the full time passed.
+    fn advance_time(&mut self, t: TimeUnits) -> bool {
+        let (date, from, to) = match &mut self.backend {
+            Some(backend) => {
+                let calendar = backend.calendar();
+                (calendar.get_date(), now(calendar), after(calendar, t))
+            }
+            None => return false,
+        };
+
+        let mut stop = to;
+        while let Some(event) = self.agenda.take_next(from, to) {
+            let text = match &mut self.backend {
+                Some(backend) => format!(
+                    "{}\n{}\n\nZeit hier anhalten?",
+                    instant_name(backend.calendar(), event.at),
+                    event.name
+                ),
+                None => return false,
+            };
+            if ask(self.main_window, "Termin", &text) {
+                stop = event.at;
+                break;
+            }
+        }
+
         if let Some(backend) = &mut self.backend {
-            backend.calendar().advance_time(*delta);
-            self.update_time();
-            self.update_date();
+            let (year, month, day, hour, minute) = stop;
+            let calendar = backend.calendar();
+            calendar.set_date(day, month, year);
+            calendar.set_time(hour, minute);
         }
+
+        self.time_changed();
+        self.show_passed_festivals(date);
+        self.update_agenda();
+
+        stop == to
+    }
+
+    pub fn add_time(&mut self, delta: &TimeUnits) {
+        self.advance_time(*delta);
     }
 
     pub fn set_date(&mut self) {
@@ -451,13 +1740,19 @@ impl Application {
             let cal = backend.calendar();
             let dialog = load("ui/date_dialog.ui") as *mut Dialog;
 
-            let year_label: *mut Label = find_child(dialog, "year").unwrap();
+            let reckonings: *mut ComboBox = find_child(dialog, "reckoning").unwrap();
             let month_label: *mut Label = find_child(dialog, "month").unwrap();
 
             let (day, month, year) = cal.get_date();
 
             unsafe {
-                (*year_label).set_text(&qt_string!(year));
+                for reckoning in cal.reckonings() {
+                    (*reckonings).add_item(&qt_string!(format!(
+                        "{} ({})",
+                        reckoning.name, reckoning.abbreviation
+                    )));
+                }
+                (*reckonings).set_current_index(cal.get_reckoning() as i32);
                 (*month_label).set_text(&qt_string!(format!(
                     "{} ({})",
                     cal.get_month_name(month),
@@ -466,34 +1761,76 @@ impl Application {
                 (*(find_child::<PushButton, _>(dialog, format!("day{}", day).as_str()).unwrap()))
                     .set_checked(true);
             }
+            update_year(dialog, cal, year);
+            update_days(dialog, cal.days_per_month(month));
 
             struct Callback {}
 
             impl Callback {
                 pub fn prev_year(
                     &mut self,
-                    (_, year, dialog, _): &mut (i32, i32, *mut Dialog, &mut PenAndPaperCalendar),
+                    (_, year, dialog, calendar): &mut (
+                        i32,
+                        i32,
+                        *mut Dialog,
+                        &mut PenAndPaperCalendar,
+                    ),
                 ) {
                     *year -= 1;
-
-                    let year_label: *mut Label = find_child(*dialog, "year").unwrap();
-                    unsafe {
-                        (*year_label).set_text(&qt_string!(*year));
-                    }
+                    update_year(*dialog, &**calendar, *year);
                 }
 
                 pub fn next_year(
                     &mut self,
-                    (_, year, dialog, _): &mut (i32, i32, *mut Dialog, &mut PenAndPaperCalendar),
+                    (_, year, dialog, calendar): &mut (
+                        i32,
+                        i32,
+                        *mut Dialog,
+                        &mut PenAndPaperCalendar,
+                    ),
                 ) {
                     *year += 1;
+                    update_year(*dialog, &**calendar, *year);
+                }
 
-                    let year_label: *mut Label = find_child(*dialog, "year").unwrap();
-                    unsafe {
-                        (*year_label).set_text(&qt_string!(*year));
+                pub fn enter_year(
+                    &mut self,
+                    (_, year, dialog, calendar): &mut (
+                        i32,
+                        i32,
+                        *mut Dialog,
+                        &mut PenAndPaperCalendar,
+                    ),
+                ) {
+                    let reckonings: *mut ComboBox = find_child(*dialog, "reckoning").unwrap();
+                    let reckoning =
+                        unsafe { calendar.reckonings()[(*reckonings).current_index() as usize] };
+
+                    if let Some(x) = input_number(
+                        *dialog as *mut Widget,
+                        "Jahr eingeben",
+                        &format!("Jahr ({}):", reckoning.name),
+                        reckoning.from_calendar(*year),
+                        -100000,
+                        100000,
+                    ) {
+                        *year = reckoning.to_calendar(x);
+                        update_year(*dialog, &**calendar, *year);
                     }
                 }
 
+                pub fn change_reckoning(
+                    &mut self,
+                    (_, year, dialog, calendar): &mut (
+                        i32,
+                        i32,
+                        *mut Dialog,
+                        &mut PenAndPaperCalendar,
+                    ),
+                ) {
+                    update_year(*dialog, &**calendar, *year);
+                }
+
                 pub fn prev_month(
                     &mut self,
                     (month, _, dialog, calendar): &mut (
@@ -505,7 +1842,7 @@ impl Application {
                 ) {
                     *month = ((*month - 2) % calendar.months_per_year()) + 1;
                     if *month < 1 {
-                        *month += 12
+                        *month += calendar.months_per_year()
                     }
 
                     let month_label: *mut Label = find_child(*dialog, "month").unwrap();
@@ -516,6 +1853,7 @@ impl Application {
                             month
                         )));
                     }
+                    update_days(*dialog, calendar.days_per_month(*month));
                 }
 
                 pub fn next_month(
@@ -537,6 +1875,7 @@ impl Application {
                             month
                         )));
                     }
+                    update_days(*dialog, calendar.days_per_month(*month));
                 }
             }
 
@@ -562,6 +1901,25 @@ impl Application {
                 &mut (i32, i32, *mut Dialog, &mut PenAndPaperCalendar)
             );
 
+            connect!(
+                find_child(dialog, "enter_year").unwrap(),
+                SIGNAL!("pressed()"),
+                &mut callback,
+                Callback,
+                Callback::enter_year,
+                &mut args,
+                &mut (i32, i32, *mut Dialog, &mut PenAndPaperCalendar)
+            );
+            connect!(
+                reckonings,
+                SIGNAL!("currentIndexChanged(int)"),
+                &mut callback,
+                Callback,
+                Callback::change_reckoning,
+                &mut args,
+                &mut (i32, i32, *mut Dialog, &mut PenAndPaperCalendar)
+            );
+
             connect!(
                 find_child(dialog, "prev_month").unwrap(),
                 SIGNAL!("pressed()"),
@@ -599,7 +1957,13 @@ impl Application {
             match result {
                 1 => {
                     calendar.set_date(day, month, year);
-                    self.update_date()
+                    calendar.set_reckoning(unsafe { (*reckonings).current_index() as usize });
+                    self.time_changed();
+
+                    // dates elsewhere follow the reckoning
+                    self.update_effects();
+                    self.update_log();
+                    self.update_agenda();
                 }
                 0 => (),
                 x => {
@@ -623,9 +1987,64 @@ impl Application {
                 minutes += calendar.hours_per_day() * calendar.minutes_per_hour();
             }
 
-            calendar.advance_time(TimeUnits::Minutes(minutes));
-            self.update_time();
-            self.update_date();
+            if self.advance_time(TimeUnits::Minutes(minutes)) {
+                self.regenerate();
+            }
+        }
+    }
+
+    fn regenerate(&mut self) {
+        if let Some(backend) = &mut self.backend {
+            if backend.player_count() == 0
+                || !ask(
+                    self.main_window,
+                    "Regeneration",
+                    "Regeneration für die Nachtruhe würfeln?",
+                )
+            {
+                return;
+            }
+
+            let pools: Vec<Stat> = match &self.sheet {
+                Some(sheet) => sheet
+                    .stats()
+                    .iter()
+                    .filter(|x| x.1.pool)
+                    .map(|x| x.1.stat.clone())
+                    .collect(),
+                None => Vec::new(),
+            };
+
+            let mut lines = Vec::new();
+            for i in 0..backend.player_count() {
+                let player = backend.get_player(i).name().clone();
+
+                let checks = backend.regenerate(i, &mut rand::thread_rng());
+                if let Some(sheet) = &self.sheet {
+                    self.caches[i].recalculate(sheet, backend.get_player(i), pools.clone());
+                }
+
+                for check in checks {
+                    let rolls: Vec<String> = check.rolls.iter().map(|x| x.to_string()).collect();
+                    lines.push(format!(
+                        "{}: {} [{}] {}",
+                        player,
+                        check.name,
+                        rolls.join(", "),
+                        check.details
+                    ));
+                    self.log
+                        .add(LogEntry::new(backend.calendar(), &player, check, false));
+                }
+            }
+
+            if lines.is_empty() {
+                lines.push("Niemand musste regenerieren.".to_string());
+            }
+
+            info(self.main_window, "Regeneration", &lines.join("\n"));
+            self.update_character_sheet();
+            self.update_log();
         }
     }
 
@@ -643,9 +2062,7 @@ impl Application {
                 minutes += calendar.hours_per_day() * calendar.minutes_per_hour();
             }
 
-            calendar.advance_time(TimeUnits::Minutes(minutes));
-            self.update_time();
-            self.update_date();
+            self.advance_time(TimeUnits::Minutes(minutes));
         }
     }
 }
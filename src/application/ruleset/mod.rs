@@ -0,0 +1,539 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use toml::value::Value;
+
+use crate::application::backend::*;
+use crate::application::formula::{Formula, FormulaError};
+
+pub enum RulesetError {
+    Io(io::Error),
+    Syntax(String),
+    Invalid(String),
+}
+
+impl fmt::Display for RulesetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RulesetError::Io(e) => write!(f, "Das Regelwerk konnte nicht gelesen werden: {}", e),
+            RulesetError::Syntax(e) => write!(f, "Das Regelwerk ist kein gültiges TOML: {}", e),
+            RulesetError::Invalid(e) => write!(f, "Das Regelwerk ist ungültig: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for RulesetError {
+    fn from(e: io::Error) -> RulesetError {
+        RulesetError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for RulesetError {
+    fn from(e: toml::de::Error) -> RulesetError {
+        RulesetError::Syntax(e.to_string())
+    }
+}
+
+// Names end up in `Stat`s and categories, which only hold static strings.
+// A ruleset is loaded once per campaign, so the leak stays small.
+fn leak(s: &str) -> &'static str {
+    Box::leak(s.to_string().into_boxed_str())
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Result<&'a Value, RulesetError> {
+    value
+        .get(key)
+        .ok_or_else(|| RulesetError::Invalid(format!("das Feld '{}' fehlt", key)))
+}
+
+fn string<'a>(value: &'a Value, key: &str) -> Result<&'a str, RulesetError> {
+    field(value, key)?
+        .as_str()
+        .ok_or_else(|| RulesetError::Invalid(format!("'{}' muss ein Text sein", key)))
+}
+
+fn integer(value: &Value, key: &str, default: i32) -> Result<i32, RulesetError> {
+    match value.get(key) {
+        Some(x) => x
+            .as_integer()
+            .map(|x| x as i32)
+            .ok_or_else(|| RulesetError::Invalid(format!("'{}' muss eine Zahl sein", key))),
+        None => Ok(default),
+    }
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> Result<&'a [Value], RulesetError> {
+    match value.get(key) {
+        Some(x) => x
+            .as_array()
+            .map(|x| x.as_slice())
+            .ok_or_else(|| RulesetError::Invalid(format!("'{}' muss eine Liste sein", key))),
+        None => Ok(&[]),
+    }
+}
+
+fn strings(value: &Value, key: &str) -> Result<Vec<String>, RulesetError> {
+    array(value, key)?
+        .iter()
+        .map(|x| {
+            x.as_str()
+                .map(|x| x.to_string())
+                .ok_or_else(|| RulesetError::Invalid(format!("'{}' darf nur Texte enthalten", key)))
+        })
+        .collect()
+}
+
+fn table<'a>(value: &'a Value, key: &str) -> Result<Vec<&'a String>, RulesetError> {
+    match value.get(key) {
+        Some(x) => x
+            .as_table()
+            .map(|x| x.keys().collect())
+            .ok_or_else(|| RulesetError::Invalid(format!("'{}' muss eine Tabelle sein", key))),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[derive(Clone)]
+pub struct RulesetOption {
+    name: String,
+    requires: Vec<(String, Vec<String>)>,
+    effects: HashMap<String, i32>,
+    limits: HashMap<String, i32>,
+    prerequisites: Vec<(String, i32)>,
+    abilities: Vec<String>,
+}
+
+impl RulesetOption {
+    fn parse(value: &Value) -> Result<RulesetOption, RulesetError> {
+        let mut requires = Vec::new();
+        for modifier in table(value, "requires")? {
+            requires.push((modifier.to_string(), strings(&value["requires"], modifier)?));
+        }
+
+        let mut effects = HashMap::new();
+        for stat in table(value, "effects")? {
+            effects.insert(stat.to_string(), integer(&value["effects"], stat, 0)?);
+        }
+
+        let mut limits = HashMap::new();
+        for limit in table(value, "limits")? {
+            limits.insert(limit.to_string(), integer(&value["limits"], limit, 0)?);
+        }
+
+        let mut prerequisites = Vec::new();
+        for stat in table(value, "prerequisites")? {
+            prerequisites.push((stat.to_string(), integer(&value["prerequisites"], stat, 0)?));
+        }
+
+        Ok(RulesetOption {
+            name: string(value, "name")?.to_string(),
+            requires: requires,
+            effects: effects,
+            limits: limits,
+            prerequisites: prerequisites,
+            abilities: strings(value, "abilities")?,
+        })
+    }
+
+    fn available(&self, p: &Player) -> bool {
+        self.requires
+            .iter()
+            .all(|(modifier, values)| values.contains(&p.get_modifier(modifier).name()))
+    }
+}
+
+impl ModifierValue for RulesetOption {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_modifier(&self, s: &Stat, _: i32) -> i32 {
+        self.effects.get(s.identifier()).cloned().unwrap_or(0)
+    }
+
+    fn get_limit(&self, name: &str) -> Option<i32> {
+        self.limits.get(name).cloned()
+    }
+
+    fn prerequisites(&self) -> Vec<(String, i32)> {
+        self.prerequisites.clone()
+    }
+
+    fn abilities(&self) -> Vec<String> {
+        self.abilities.clone()
+    }
+}
+
+pub struct StatData {
+    pub stat: Stat,
+    pub min: i32,
+    pub max: i32,
+    pub default: i32,
+    pub cost: Option<String>,
+    pub pool: bool,
+    // how much of a pool a rest restores, e.g. "1W6"
+    pub regeneration: Option<String>,
+    pub formula: Option<Formula>,
+    // what the stat is used for in the backend's rules, e.g. "attack"
+    pub action: Option<String>,
+    // what kind of stat it is in the backend's rules, e.g. "talent"
+    pub kind: Option<String>,
+}
+
+enum EntryData {
+    Stat(StatData),
+    Modifier(&'static str, Rc<Vec<RulesetOption>>),
+}
+
+struct CategoryData {
+    name: &'static str,
+    entries: Vec<EntryData>,
+}
+
+pub struct Ruleset {
+    pub name: String,
+    categories: Vec<CategoryData>,
+}
+
+impl Ruleset {
+    fn parse_entry(value: &Value) -> Result<EntryData, RulesetError> {
+        if value.get("modifier").is_some() {
+            let options = array(value, "option")?
+                .iter()
+                .map(RulesetOption::parse)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            return Ok(EntryData::Modifier(
+                leak(string(value, "modifier")?),
+                Rc::new(options),
+            ));
+        }
+
+        let stat = if value.get("attribute").is_some() {
+            Stat::Attribute(
+                leak(string(value, "attribute")?),
+                leak(string(value, "short")?),
+            )
+        } else if value.get("ability").is_some() {
+            Stat::Ability(
+                leak(string(value, "ability")?),
+                strings(value, "check")?.iter().map(|x| leak(x)).collect(),
+            )
+        } else if value.get("calculated").is_some() {
+            Stat::Calculated(leak(string(value, "calculated")?))
+        } else {
+            return Err(RulesetError::Invalid(
+                "ein Eintrag muss ein Modifikator, eine Eigenschaft, eine Fertigkeit oder ein berechneter Wert sein".to_string(),
+            ));
+        };
+
+        Ok(EntryData::Stat(StatData {
+            min: integer(value, "min", 0)?,
+            max: integer(value, "max", 0)?,
+            default: integer(value, "default", 0)?,
+            cost: match value.get("cost") {
+                Some(_) => Some(string(value, "cost")?.to_string()),
+                None => None,
+            },
+            pool: match value.get("pool") {
+                Some(x) => x.as_bool().ok_or_else(|| {
+                    RulesetError::Invalid("'pool' muss ein Wahrheitswert sein".to_string())
+                })?,
+                None => false,
+            },
+            regeneration: match value.get("regeneration") {
+                Some(_) => Some(string(value, "regeneration")?.to_string()),
+                None => None,
+            },
+            formula: match value.get("formula") {
+                Some(_) => Some(
+                    string(value, "formula")?
+                        .parse()
+                        .map_err(|e: FormulaError| {
+                            RulesetError::Invalid(format!("'{}': {}", stat.identifier(), e))
+                        })?,
+                ),
+                None => None,
+            },
+            action: match value.get("action") {
+                Some(_) => Some(string(value, "action")?.to_string()),
+                None => None,
+            },
+            kind: match value.get("kind") {
+                Some(_) => Some(string(value, "kind")?.to_string()),
+                None => None,
+            },
+            stat: stat,
+        }))
+    }
+
+    // Formulas may only reference stats of the ruleset and must not depend
+    // on their own value
+    fn check_formulas(&self) -> Result<(), RulesetError> {
+        fn visit<'a>(
+            name: &'a str,
+            formulas: &'a HashMap<&'static str, Vec<String>>,
+            path: &mut Vec<&'a str>,
+            done: &mut HashSet<&'a str>,
+        ) -> Result<(), RulesetError> {
+            if done.contains(name) {
+                return Ok(());
+            }
+            if let Some(index) = path.iter().position(|x| *x == name) {
+                let mut cycle = path[index..].to_vec();
+                cycle.push(name);
+                return Err(RulesetError::Invalid(format!(
+                    "zyklische Formel {}",
+                    cycle.join(" -> ")
+                )));
+            }
+
+            if let Some(references) = formulas.get(name) {
+                path.push(name);
+                for reference in references {
+                    visit(reference, formulas, path, done)?;
+                }
+                path.pop();
+            }
+
+            done.insert(name);
+            Ok(())
+        }
+
+        let stats = self.stats();
+        let formulas: HashMap<&'static str, Vec<String>> = stats
+            .iter()
+            .filter_map(|x| Some((x.stat.identifier(), x.formula.as_ref()?.references())))
+            .collect();
+
+        for (name, references) in &formulas {
+            if let Some(unknown) = references
+                .iter()
+                .find(|x| !stats.iter().any(|s| s.stat.identifier() == x.as_str()))
+            {
+                return Err(RulesetError::Invalid(format!(
+                    "die Formel von '{}' verweist auf den unbekannten Wert '{}'",
+                    name, unknown
+                )));
+            }
+        }
+
+        let mut done = HashSet::new();
+        for name in formulas.keys() {
+            visit(name, &formulas, &mut Vec::new(), &mut done)?;
+        }
+
+        Ok(())
+    }
+
+    // Options may only require modifiers declared before them, and only
+    // affect and require stats of the ruleset
+    fn check_modifiers(&self) -> Result<(), RulesetError> {
+        let stats = self.stats();
+        let is_stat = |name: &str| stats.iter().any(|s| s.stat.identifier() == name);
+
+        let mut declared: Vec<(&str, &Vec<RulesetOption>)> = Vec::new();
+        for entry in self.categories.iter().flat_map(|x| x.entries.iter()) {
+            let (name, options) = match entry {
+                EntryData::Modifier(name, options) => (*name, options),
+                EntryData::Stat(_) => continue,
+            };
+
+            for option in options.iter() {
+                let invalid = |message: String| {
+                    Err(RulesetError::Invalid(format!(
+                        "'{}' von '{}' {}",
+                        option.name, name, message
+                    )))
+                };
+
+                for (modifier, values) in &option.requires {
+                    let required = match declared.iter().find(|x| x.0 == modifier) {
+                        Some(x) => x.1,
+                        None => {
+                            return invalid(format!(
+                                "setzt den unbekannten Modifikator '{}' voraus",
+                                modifier
+                            ))
+                        }
+                    };
+                    if let Some(unknown) = values
+                        .iter()
+                        .find(|x| !required.iter().any(|o| o.name == **x))
+                    {
+                        return invalid(format!(
+                            "setzt die unbekannte Option '{}' von '{}' voraus",
+                            unknown, modifier
+                        ));
+                    }
+                }
+                if let Some(unknown) = option.effects.keys().find(|x| !is_stat(x)) {
+                    return invalid(format!("verändert den unbekannten Wert '{}'", unknown));
+                }
+                if let Some(unknown) = option.prerequisites.iter().find(|x| !is_stat(&x.0)) {
+                    return invalid(format!("setzt den unbekannten Wert '{}' voraus", unknown.0));
+                }
+            }
+
+            declared.push((name, options));
+        }
+
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Ruleset, RulesetError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn stats(&self) -> Vec<&StatData> {
+        self.categories
+            .iter()
+            .flat_map(|x| x.entries.iter())
+            .filter_map(|x| match x {
+                EntryData::Stat(stat) => Some(stat),
+                EntryData::Modifier(_, _) => None,
+            })
+            .collect()
+    }
+
+    pub fn defaults(&self) -> HashMap<Stat, i32> {
+        self.stats()
+            .into_iter()
+            .filter(|x| x.default != 0)
+            .map(|x| (x.stat.clone(), x.default))
+            .collect()
+    }
+
+    pub fn character_sheet(
+        &self,
+        calc: Box<
+            Fn(&CharacterSheet, &Player, &StatCategory, &Stat, &Fn(&Stat) -> i32) -> Breakdown,
+        >,
+    ) -> CharacterSheet {
+        let mut sheet = CharacterSheet::new(calc);
+
+        for data in &self.categories {
+            let mut category = StatCategory::new(data.name);
+
+            for entry in &data.entries {
+                match entry {
+                    EntryData::Stat(stat) => category.add_stat(StatDescription {
+                        stat: stat.stat.clone(),
+                        min: stat.min,
+                        max: stat.max,
+                        pool: stat.pool,
+                        formula: stat.formula.clone(),
+                    }),
+                    EntryData::Modifier(name, options) => {
+                        let options = options.clone();
+                        category.add_modifier(Modifier::new(
+                            name,
+                            Box::new(move |p: &Player| {
+                                options
+                                    .iter()
+                                    .filter(|x| x.available(p))
+                                    .map(|x| Box::new(x.clone()) as Box<ModifierValue>)
+                                    .collect()
+                            }),
+                        ));
+                    }
+                }
+            }
+
+            sheet.add_category(category);
+        }
+
+        sheet
+    }
+}
+
+impl FromStr for Ruleset {
+    type Err = RulesetError;
+
+    fn from_str(s: &str) -> Result<Ruleset, RulesetError> {
+        let ruleset: Value = s.parse()?;
+
+        let mut categories = Vec::new();
+        for category in array(&ruleset, "category")? {
+            categories.push(CategoryData {
+                name: leak(string(category, "name")?),
+                entries: array(category, "entry")?
+                    .iter()
+                    .map(Ruleset::parse_entry)
+                    .collect::<Result<Vec<_>, _>>()?,
+            });
+        }
+
+        let ruleset = Ruleset {
+            name: string(&ruleset, "name")?.to_string(),
+            categories: categories,
+        };
+        ruleset.check_formulas()?;
+        ruleset.check_modifiers()?;
+
+        Ok(ruleset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> String {
+        match source.parse::<Ruleset>() {
+            Ok(_) => String::new(),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn stats(formulas: &[(&str, &str)]) -> String {
+        let mut source = "name = \"Test\"\n\n[[category]]\nname = \"Werte\"\n".to_string();
+        for (stat, formula) in formulas {
+            source += &format!(
+                "\n[[category.entry]]\ncalculated = \"{}\"\nformula = \"{}\"\n",
+                stat, formula
+            );
+        }
+        source
+    }
+
+    #[test]
+    fn accepts_acyclic_formulas() {
+        let source = stats(&[("A", "1"), ("B", "A * 2"), ("C", "A + B")]);
+        assert_eq!(error(&source), "");
+    }
+
+    #[test]
+    fn rejects_cyclic_formulas() {
+        let source = stats(&[("A", "C + 1"), ("B", "A * 2"), ("C", "B / 2")]);
+        assert!(error(&source).starts_with("Das Regelwerk ist ungültig: zyklische Formel"));
+
+        let source = stats(&[("A", "A + 1")]);
+        assert_eq!(
+            error(&source),
+            "Das Regelwerk ist ungültig: zyklische Formel A -> A"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_references() {
+        let source = stats(&[("A", "1"), ("B", "A + X")]);
+        assert_eq!(
+            error(&source),
+            "Das Regelwerk ist ungültig: die Formel von 'B' verweist auf den unbekannten Wert 'X'"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_formulas() {
+        let source = stats(&[("A", "1 +")]);
+        assert_eq!(
+            error(&source),
+            "Das Regelwerk ist ungültig: 'A': Ungültige Formel: unerwartetes Ende an Position 3 in '1 +'"
+        );
+    }
+}
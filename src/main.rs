@@ -12,7 +12,9 @@ use qt_core::string::String;
 use qt_widgets::list_view::ListView;
 
 use crate::qt_bind::*;
-use application::backend::TimeUnits;
+use application::backend::{BackendRegistry, TimeUnits};
+use application::dnd::DNDBackend;
+use application::dsa::DSABackend;
 use application::Application;
 
 macro_rules! version {
@@ -43,7 +45,11 @@ fn main() {
             (*main_window).show();
         }
 
-        let mut backend = Application::new(main_window);
+        let mut registry = BackendRegistry::new();
+        registry.register(DSABackend::factory());
+        registry.register(DNDBackend::factory());
+
+        let mut backend = Application::new(main_window, registry);
 
         connect!(
             find_child(main_window, "new_file").unwrap(),
@@ -59,6 +65,20 @@ fn main() {
             Application,
             Application::open
         );
+        connect!(
+            find_child(main_window, "save").unwrap(),
+            SIGNAL!("triggered()"),
+            &mut backend,
+            Application,
+            Application::save
+        );
+        connect!(
+            find_child(main_window, "save_as").unwrap(),
+            SIGNAL!("triggered()"),
+            &mut backend,
+            Application,
+            Application::save_as
+        );
         connect!(
             find_child(main_window, "close").unwrap(),
             SIGNAL!("triggered()"),
@@ -109,6 +129,65 @@ fn main() {
             Application::remove_player
         );
 
+        connect!(
+            find_child(main_window, "add_condition").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::add_condition
+        );
+        connect!(
+            find_child(main_window, "reduce_condition").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::reduce_condition
+        );
+        connect!(
+            find_child(main_window, "remove_condition").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::remove_condition
+        );
+
+        connect!(
+            find_child(main_window, "validate").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::validate
+        );
+        connect!(
+            find_child(main_window, "add_effect").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::add_effect
+        );
+        connect!(
+            find_child(main_window, "remove_effect").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::remove_effect
+        );
+
+        connect!(
+            find_child(main_window, "add_event").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::add_event
+        );
+        connect!(
+            find_child(main_window, "remove_event").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::remove_event
+        );
+
         connect!(
             find_child(main_window, "set_date").unwrap(),
             SIGNAL!("pressed()"),
@@ -187,6 +266,57 @@ fn main() {
             Application::next_evening
         );
 
+        connect!(
+            find_child(main_window, "start_combat").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::start_combat
+        );
+        connect!(
+            find_child(main_window, "add_npc").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::add_npc
+        );
+        connect!(
+            find_child(main_window, "next_turn").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::next_turn
+        );
+        connect!(
+            find_child(main_window, "end_combat").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::end_combat
+        );
+
+        connect!(
+            find_child(main_window, "log_filter").unwrap(),
+            SIGNAL!("currentIndexChanged(int)"),
+            &mut backend,
+            Application,
+            Application::filter_log
+        );
+        connect!(
+            find_child(main_window, "toggle_hidden").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::toggle_hidden
+        );
+        connect!(
+            find_child(main_window, "export_log").unwrap(),
+            SIGNAL!("pressed()"),
+            &mut backend,
+            Application,
+            Application::export_log
+        );
+
         qt_widgets::application::Application::exec()
     })
 }
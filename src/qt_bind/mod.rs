@@ -28,9 +28,15 @@ use qt_ui_tools::ui_loader::UiLoader;
 
 use qt_core::list::ListModelIndex;
 use qt_core::model_index::ModelIndex;
+use qt_core::qt::WindowType;
 
+use qt_widgets::combo_box::ComboBox;
+use qt_widgets::dialog::Dialog;
+use qt_widgets::file_dialog::FileDialog;
 use qt_widgets::input_dialog::InputDialog;
+use qt_widgets::label::Label;
 use qt_widgets::line_edit::EchoMode;
+use qt_widgets::message_box::{MessageBox, StandardButton};
 
 extern "C" {
     fn create(
@@ -336,3 +342,119 @@ pub fn input(
         false => None,
     }
 }
+
+pub fn input_number(
+    window: *mut Widget,
+    title: &str,
+    label: &str,
+    value: i32,
+    min: i32,
+    max: i32,
+) -> Option<i32> {
+    let mut ok = false;
+    let number = unsafe {
+        InputDialog::get_int((
+            window,
+            &qt_string!(title),
+            &qt_string!(label),
+            value,
+            min,
+            max,
+            1,
+            &mut ok as *mut bool,
+        ))
+    };
+
+    match ok {
+        true => Some(number),
+        false => None,
+    }
+}
+
+// Always asks, even if there is just one item, and returns the index of the
+// chosen one
+pub fn choose(
+    window: *mut Widget,
+    title: &str,
+    label: &str,
+    items: &[std::string::String],
+) -> Option<usize> {
+    let dialog = load("ui/choose_dialog.ui") as *mut Dialog;
+    let text: *mut Label = find_child(dialog, "label").unwrap();
+    let combobox: *mut ComboBox = find_child(dialog, "items").unwrap();
+
+    let (result, index) = unsafe {
+        (*dialog).set_parent((window, Flags::from_enum(WindowType::Dialog)));
+        (*dialog).set_window_title(&qt_string!(title));
+        (*text).set_text(&qt_string!(label));
+        for item in items {
+            (*combobox).add_item(&qt_string!(item));
+        }
+
+        ((*dialog).exec(), (*combobox).current_index())
+    };
+    delete(as_object(dialog));
+
+    match result {
+        1 if index >= 0 => Some(index as usize),
+        _ => None,
+    }
+}
+
+pub fn open_file_name(
+    window: *mut Widget,
+    title: &str,
+    filter: &str,
+) -> Option<std::string::String> {
+    let name = unsafe {
+        FileDialog::get_open_file_name((
+            window,
+            &qt_string!(title),
+            &qt_string!(""),
+            &qt_string!(filter),
+        ))
+    };
+
+    match name.is_empty() {
+        true => None,
+        false => Some(name.to_std_string()),
+    }
+}
+
+pub fn save_file_name(
+    window: *mut Widget,
+    title: &str,
+    filter: &str,
+) -> Option<std::string::String> {
+    let name = unsafe {
+        FileDialog::get_save_file_name((
+            window,
+            &qt_string!(title),
+            &qt_string!(""),
+            &qt_string!(filter),
+        ))
+    };
+
+    match name.is_empty() {
+        true => None,
+        false => Some(name.to_std_string()),
+    }
+}
+
+pub fn error(window: *mut Widget, title: &str, text: &str) {
+    unsafe {
+        MessageBox::critical((window, &qt_string!(title), &qt_string!(text)));
+    }
+}
+
+pub fn info(window: *mut Widget, title: &str, text: &str) {
+    unsafe {
+        MessageBox::information((window, &qt_string!(title), &qt_string!(text)));
+    }
+}
+
+pub fn ask(window: *mut Widget, title: &str, text: &str) -> bool {
+    unsafe {
+        MessageBox::question((window, &qt_string!(title), &qt_string!(text))) == StandardButton::Yes
+    }
+}